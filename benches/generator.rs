@@ -0,0 +1,94 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sudoku_generator::batch::{generate_batch, BatchOptions};
+use sudoku_generator::bitboard::BitBoard;
+use sudoku_generator::board::{game_str_to_vec, Board};
+use sudoku_generator::generator::Generator;
+use sudoku_generator::solver::{Backtracking, Dlx, Parallel, Solver};
+
+// puzzles of increasing difficulty, the last one has many solutions
+const GAMES: [(&str, &str); 4] = [
+    (
+        "easy",
+        ".......1..14...3.9...6......79...6..5...6......324.5........8.2.2.519.67....7....",
+    ),
+    (
+        "hard",
+        "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......",
+    ),
+    (
+        "extreme",
+        "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+    ),
+    (
+        "multiple",
+        "....4..6..6275.4..7......38..32...4..7.....8..8...13.....5..........6...8...3..7.",
+    ),
+];
+
+fn bench_generate(c: &mut Criterion) {
+    c.bench_function("generate_game + dig_holes", |b| {
+        let mut generator = Generator::from_seed(1);
+        b.iter(|| {
+            let game = generator.generate_game();
+            black_box(generator.dig_holes(&game))
+        })
+    });
+}
+
+fn bench_batch(c: &mut Criterion) {
+    c.bench_function("generate_batch 64", |b| {
+        b.iter(|| {
+            let options = BatchOptions {
+                seed: 1,
+                ..BatchOptions::default()
+            };
+            black_box(generate_batch(64, options).count())
+        })
+    });
+}
+
+fn bench_solve(c: &mut Criterion) {
+    for (name, game) in GAMES {
+        let game_vec = game_str_to_vec(game).unwrap();
+        c.bench_function(&format!("board solve {}", name), |b| {
+            b.iter(|| {
+                let mut board = Board::new();
+                board.init(&game_vec).unwrap();
+                black_box(board.solve())
+            })
+        });
+
+        let mut board = Board::new();
+        board.load_game(&game_vec).unwrap();
+        c.bench_function(&format!("backtracking count {}", name), |b| {
+            b.iter(|| black_box(Backtracking.count_solutions(&board, 100).unwrap()))
+        });
+        c.bench_function(&format!("dlx count {}", name), |b| {
+            b.iter(|| black_box(Dlx.count_solutions(&board, 100).unwrap()))
+        });
+    }
+}
+
+// counting many solutions, with more and more threads
+fn bench_parallel(c: &mut Criterion) {
+    let board = BitBoard::from_game(&game_str_to_vec(GAMES[3].1).unwrap()).unwrap();
+    let mut threads = vec![1, 2, 4];
+    if !threads.contains(&num_cpus::get()) {
+        threads.push(num_cpus::get());
+    }
+    for n in threads {
+        let parallel = Parallel::new(n);
+        c.bench_function(&format!("parallel count multiple {} threads", n), |b| {
+            b.iter(|| black_box(parallel.search(&board, 100_000, &|_| {}).unwrap()))
+        });
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_generate,
+    bench_batch,
+    bench_solve,
+    bench_parallel
+);
+criterion_main!(benches);
@@ -0,0 +1,150 @@
+//! Async versions of the generator for tokio applications. The search runs on the blocking
+//! pool, so it never stalls the runtime, and stops early when its token is cancelled.
+
+use crate::error::SudokuError;
+use crate::generator::{Generator, Progress};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::panic;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone, Default)]
+pub struct AsyncOptions {
+    // None to seed the generator from entropy
+    pub seed: Option<u64>,
+    // cancelling it stops the generation at the next restart or cell dug
+    pub cancel: CancellationToken,
+    // receives the restarts and the cells dug so far
+    pub progress: Option<UnboundedSender<Progress>>,
+}
+
+impl AsyncOptions {
+    fn generator(&self) -> Generator {
+        match self.seed {
+            Some(seed) => Generator::from_seed(seed),
+            None => Generator::new(ChaCha8Rng::from_entropy()),
+        }
+    }
+
+    // sends the progress, tells the generator whether to go on
+    fn observe(&self, progress: Progress) -> bool {
+        if let Some(sender) = &self.progress {
+            // nobody listening is not a reason to stop
+            let _ = sender.send(progress);
+        }
+        !self.cancel.is_cancelled()
+    }
+}
+
+/**
+ * `Generator::generate_game` on the blocking pool. Fails with `SudokuError::Cancelled` if the
+ * token is cancelled before a game is found.
+ */
+pub async fn generate_game_async(options: AsyncOptions) -> Result<String, SudokuError> {
+    if options.cancel.is_cancelled() {
+        return Err(SudokuError::Cancelled);
+    }
+    run_blocking(move || {
+        options
+            .generator()
+            .generate_game_with(&mut |progress| options.observe(progress))
+    })
+    .await
+}
+
+/**
+ * `Generator::dig_holes` on the blocking pool. Fails with `SudokuError::Cancelled` if the token
+ * is cancelled before the digging is done.
+ */
+pub async fn dig_holes_async(game: String, options: AsyncOptions) -> Result<String, SudokuError> {
+    if options.cancel.is_cancelled() {
+        return Err(SudokuError::Cancelled);
+    }
+    run_blocking(move || {
+        options
+            .generator()
+            .dig_holes_with(&game, &mut |progress| options.observe(progress))
+    })
+    .await
+}
+
+// run a search on the blocking pool, a panic in it is resumed in the caller instead of lost
+async fn run_blocking<T: Send + 'static>(
+    search: impl FnOnce() -> Result<T, SudokuError> + Send + 'static,
+) -> Result<T, SudokuError> {
+    task::spawn_blocking(search)
+        .await
+        .unwrap_or_else(|error| match error.try_into_panic() {
+            Ok(panic) => panic::resume_unwind(panic),
+            // the runtime is shutting down
+            Err(_) => Err(SudokuError::Cancelled),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dig_holes_async, generate_game_async, run_blocking, AsyncOptions};
+    use crate::error::SudokuError;
+    use crate::generator::{Generator, Progress};
+    use tokio::sync::mpsc;
+
+    fn seeded(seed: u64) -> AsyncOptions {
+        AsyncOptions {
+            seed: Some(seed),
+            ..AsyncOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_game_async() {
+        let game = generate_game_async(seeded(3)).await.unwrap();
+        assert_eq!(game, Generator::from_seed(3).generate_game());
+
+        let puzzle = dig_holes_async(game.clone(), seeded(3)).await.unwrap();
+        assert_eq!(Ok(puzzle), Generator::from_seed(3).dig_holes(&game));
+    }
+
+    #[tokio::test]
+    async fn test_progress() {
+        let game = Generator::from_seed(5).generate_game();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let options = AsyncOptions {
+            progress: Some(sender),
+            ..seeded(5)
+        };
+        let puzzle = dig_holes_async(game, options).await.unwrap();
+
+        let mut dug = Vec::new();
+        while let Some(progress) = receiver.recv().await {
+            match progress {
+                Progress::Dug(count) => dug.push(count),
+                Progress::Restart(_) => panic!("digging never restarts"),
+            }
+        }
+        let holes = puzzle.chars().filter(|c| *c == '0').count();
+        assert_eq!(dug, (1..=holes).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let options = seeded(1);
+        options.cancel.cancel();
+        assert_eq!(
+            generate_game_async(options.clone()).await,
+            Err(SudokuError::Cancelled)
+        );
+        let game = Generator::from_seed(1).generate_game();
+        assert_eq!(
+            dig_holes_async(game, options).await,
+            Err(SudokuError::Cancelled)
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "search failed")]
+    async fn test_panic() {
+        let _ = run_blocking::<String>(|| panic!("search failed")).await;
+    }
+}
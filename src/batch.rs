@@ -0,0 +1,216 @@
+use crate::board::game_str_to_vec;
+use crate::difficulty::Difficulty;
+use crate::generator::Generator;
+use crate::puzzle::Puzzle;
+use crate::symmetry::canonical_form;
+use rand::{thread_rng, Rng};
+use std::collections::{BTreeMap, HashSet};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    // one thread per cpu by default
+    pub threads: usize,
+    // the k-th puzzle started is generated from `seed + k`
+    pub seed: u64,
+    // None for the puzzles of `dig_holes`, else see `Generator::generate_puzzle`
+    pub difficulty: Option<Difficulty>,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions {
+            threads: num_cpus::get(),
+            seed: thread_rng().gen(),
+            difficulty: None,
+        }
+    }
+}
+
+// the puzzles started so far, the finished ones waiting for those before them, and the grids of
+// those kept
+struct Progress {
+    started: usize,
+    kept: usize,
+    // the next puzzle to keep or drop, in the order they were started
+    next: usize,
+    pending: BTreeMap<usize, (Puzzle, Vec<u8>)>,
+    grids: HashSet<Vec<u8>>,
+    // the iterator was dropped
+    closed: bool,
+}
+
+/**
+ * Generate `n` puzzles on `options.threads` threads. The k-th puzzle started gets its own seed,
+ * so it does not depend on the thread that made it and `Generator::from_seed(puzzle.seed)` makes
+ * it again. A puzzle whose solution is equivalent to the solution of a puzzle with a lower seed,
+ * see `symmetry::canonical_form`, is dropped and one more is started. Puzzles are streamed in
+ * the order of their seeds, each one as soon as it and those before it are ready, so a batch is
+ * the same whatever the number of threads. Dropping the iterator stops the threads once they
+ * finish their current puzzle, and a panic in a thread is resumed by the iterator.
+ */
+pub fn generate_batch(n: usize, options: BatchOptions) -> impl Iterator<Item = Puzzle> {
+    run_batch(n, options, generate_one)
+}
+
+// `generate_batch` with the puzzle of each seed made by `generate`
+fn run_batch(
+    n: usize,
+    options: BatchOptions,
+    generate: fn(u64, Option<Difficulty>) -> Puzzle,
+) -> impl Iterator<Item = Puzzle> {
+    let (sender, receiver) = mpsc::channel::<thread::Result<Puzzle>>();
+    let progress = Arc::new(Mutex::new(Progress {
+        started: 0,
+        kept: 0,
+        next: 0,
+        pending: BTreeMap::new(),
+        grids: HashSet::new(),
+        closed: false,
+    }));
+
+    for _ in 0..options.threads.max(1) {
+        let sender = sender.clone();
+        let progress = Arc::clone(&progress);
+        let options = options.clone();
+        thread::spawn(move || loop {
+            let k = {
+                let mut progress = progress.lock().unwrap();
+                if progress.kept >= n || progress.closed {
+                    break;
+                }
+                progress.started += 1;
+                progress.started - 1
+            };
+            let seed = options.seed.wrapping_add(k as u64);
+            let generated = panic::catch_unwind(AssertUnwindSafe(|| {
+                let puzzle = generate(seed, options.difficulty);
+                let grid = canonical_form(&game_str_to_vec(&puzzle.solution).unwrap());
+                (puzzle, grid)
+            }));
+            // the puzzle of this seed will never come, the iterator stops waiting for it
+            let (puzzle, grid) = match generated {
+                Ok(generated) => generated,
+                Err(panic) => {
+                    progress.lock().unwrap().closed = true;
+                    let _ = sender.send(Err(panic));
+                    break;
+                }
+            };
+
+            // keep or drop the puzzles whose turn has come, the lowest seed wins a duplicate
+            let mut progress = progress.lock().unwrap();
+            progress.pending.insert(k, (puzzle, grid));
+            loop {
+                let next = progress.next;
+                let Some((puzzle, grid)) = progress.pending.remove(&next) else {
+                    break;
+                };
+                progress.next += 1;
+                if progress.kept < n && !progress.closed && progress.grids.insert(grid) {
+                    progress.kept += 1;
+                    progress.closed = sender.send(Ok(puzzle)).is_err();
+                }
+            }
+        });
+    }
+    receiver
+        .into_iter()
+        .map(|puzzle| puzzle.unwrap_or_else(|panic| panic::resume_unwind(panic)))
+}
+
+fn generate_one(seed: u64, difficulty: Option<Difficulty>) -> Puzzle {
+    let start = Instant::now();
+    let mut generator = Generator::from_seed(seed);
+    let givens = match difficulty {
+        None => {
+            let game = generator.generate_game();
+            generator.dig_holes(&game).unwrap()
+        }
+        Some(difficulty) => generator.generate_puzzle(difficulty).0,
+    };
+    // generated puzzles have a unique solution
+    Puzzle::new(givens, seed, start.elapsed()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_batch, generate_one, run_batch, BatchOptions};
+    use crate::board::game_str_to_vec;
+    use crate::difficulty::{grade, Difficulty};
+    use crate::generator::Generator;
+    use crate::puzzle::Puzzle;
+    use crate::symmetry::canonical_form;
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    fn options(threads: usize, seed: u64) -> BatchOptions {
+        BatchOptions {
+            threads,
+            seed,
+            difficulty: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_batch() {
+        let puzzles: Vec<_> = generate_batch(20, options(4, 100)).collect();
+        assert_eq!(puzzles.len(), 20);
+        assert!(puzzles.windows(2).all(|pair| pair[0].seed < pair[1].seed));
+
+        let grids: HashSet<Vec<u8>> = puzzles
+            .iter()
+            .map(|p| canonical_form(&game_str_to_vec(&p.solution).unwrap()))
+            .collect();
+        assert_eq!(grids.len(), 20);
+
+        for puzzle in &puzzles {
+            let mut generator = Generator::from_seed(puzzle.seed);
+            let game = generator.generate_game();
+            assert_eq!(game, puzzle.solution);
+            assert_eq!(generator.dig_holes(&game), Ok(puzzle.givens.clone()));
+        }
+    }
+
+    #[test]
+    fn test_batch_reproducible() {
+        let mut first: Vec<_> = generate_batch(8, options(3, 7)).collect();
+        let mut second: Vec<_> = generate_batch(8, options(1, 7)).collect();
+        for puzzle in first.iter_mut().chain(&mut second) {
+            puzzle.generation_time = Duration::ZERO;
+        }
+        assert_eq!(first, second);
+        assert_eq!(generate_batch(0, options(2, 7)).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "no puzzle for seed 3")]
+    fn test_batch_panic() {
+        fn generate(seed: u64, difficulty: Option<Difficulty>) -> Puzzle {
+            assert_ne!(seed, 3, "no puzzle for seed 3");
+            generate_one(seed, difficulty)
+        }
+        // the puzzles after seed 3 never come, the iterator fails instead of waiting
+        let _ = run_batch(10, options(2, 0), generate).count();
+    }
+
+    #[test]
+    fn test_batch_difficulty() {
+        let options = BatchOptions {
+            threads: 2,
+            seed: 4,
+            difficulty: Some(Difficulty::Medium),
+        };
+        for puzzle in generate_batch(2, options) {
+            let givens = game_str_to_vec(&puzzle.givens).unwrap();
+            assert_eq!(grade(&givens), Ok(Difficulty::Medium));
+            assert_eq!(puzzle.difficulty, Difficulty::Medium);
+            assert_eq!(puzzle.clues, givens.iter().filter(|v| **v > 0).count());
+            let solution = game_str_to_vec(&puzzle.solution).unwrap();
+            assert!(givens.iter().zip(&solution).all(|(g, s)| *g == 0 || g == s));
+        }
+    }
+}
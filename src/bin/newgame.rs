@@ -1,11 +1,522 @@
-use sudoku_generator::generator;
-
-fn main() {
-  // for _ in 0..100 {
-  //   let game = generator::generate_game();
-  //   println!("game: {}", game);
-    let game = "628519437579423618413786529857932146362147895194865273781354962235691784946278351";
-    let puzzle = generator::dig_holes(&game);
-    println!("puzz: {}", puzzle);
-  // }
-}
\ No newline at end of file
+//! Command line access to the generator and solvers, reading puzzles one per line from a file or
+//! stdin so it fits in shell pipelines, see `collection` for the lines read:
+//!
+//! ```text
+//! newgame generate --count 10 --difficulty hard --seed 42
+//! newgame generate | newgame solve --format ss
+//! newgame generate --size 16 | newgame solve --size 16
+//! newgame rate puzzles.txt
+//! newgame collection puzzles.txt --table csv > results.csv
+//! ```
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+use sudoku_generator::batch::{generate_batch, BatchOptions};
+use sudoku_generator::board::{check_game, game_str_to_vec, Board};
+use sudoku_generator::collection::{
+    parse_line, process_collection, write_results, CollectionOptions,
+};
+use sudoku_generator::difficulty::Difficulty;
+use sudoku_generator::error::SudokuError;
+use sudoku_generator::formats::{opensudoku, Format};
+use sudoku_generator::generator::Generator;
+use sudoku_generator::grid::{Geometry, Grid};
+use sudoku_generator::parser::ParseMode;
+use sudoku_generator::pencilmarks;
+use sudoku_generator::rating::rate;
+use sudoku_generator::solver::{Dlx, Parallel, Solver};
+
+#[derive(Parser)]
+#[command(about = "Generate, solve and rate sudoku puzzles")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate puzzles with a unique solution
+    Generate {
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// The first seed, puzzle k is made from seed + k
+        #[arg(long)]
+        seed: Option<u64>,
+        #[arg(long, value_enum)]
+        difficulty: Option<Level>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Line)]
+        format: OutputFormat,
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Generate grids of this side instead, such as 4, 6, 12, 16 or 25, written as lines
+        #[arg(long, conflicts_with_all = ["difficulty", "format", "threads"])]
+        size: Option<usize>,
+    },
+    /// Print the solution of each puzzle
+    Solve {
+        file: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Line)]
+        format: OutputFormat,
+        /// Search on this many threads, see `solver::Parallel`
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Read grids of this side instead, one line each, and write their solutions as lines
+        #[arg(long, conflicts_with_all = ["format", "threads"])]
+        size: Option<usize>,
+    },
+    /// Print the number of solutions of each puzzle
+    Count {
+        file: Option<PathBuf>,
+        /// Stop counting at this many solutions
+        #[arg(long, default_value_t = 1000)]
+        limit: usize,
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+    /// Print the Sudoku Explainer rating, the difficulty and the hardest technique of each puzzle
+    Rate { file: Option<PathBuf> },
+    /// Check that each puzzle has no conflict and a unique solution
+    Validate { file: Option<PathBuf> },
+    /// Write the puzzles in another format
+    Convert {
+        file: Option<PathBuf>,
+        #[arg(long, value_enum)]
+        format: OutputFormat,
+    },
+    /// Solve, count and rate a whole collection, one row of results per puzzle
+    Collection {
+        file: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Table::Tsv)]
+        table: Table,
+        /// Only solve and count, rating takes much longer
+        #[arg(long)]
+        no_rate: bool,
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+    /// Time the generation of puzzles
+    Bench {
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+        #[arg(long)]
+        seed: Option<u64>,
+        #[arg(long, value_enum)]
+        difficulty: Option<Level>,
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Level {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Extreme,
+}
+
+impl From<Level> for Difficulty {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Easy => Difficulty::Easy,
+            Level::Medium => Difficulty::Medium,
+            Level::Hard => Difficulty::Hard,
+            Level::Expert => Difficulty::Expert,
+            Level::Extreme => Difficulty::Extreme,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Table {
+    Tsv,
+    Csv,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+enum OutputFormat {
+    /// 81 characters, `.` for the blanks
+    Line,
+    /// A pencil-mark grid
+    Pm,
+    Sdk,
+    Sdx,
+    Ss,
+    Opensudoku,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Generate {
+            count,
+            seed,
+            difficulty,
+            format,
+            threads,
+            size: None,
+        } => generate(count, batch_options(seed, difficulty, threads), format),
+        Command::Generate {
+            count,
+            seed,
+            size: Some(size),
+            ..
+        } => generate_grids(count, seed, size),
+        Command::Solve {
+            file,
+            format,
+            threads,
+            size: None,
+        } => solve(file.as_deref(), format, threads),
+        Command::Solve {
+            file,
+            size: Some(size),
+            ..
+        } => solve_grids(file.as_deref(), size),
+        Command::Count {
+            file,
+            limit,
+            threads,
+        } => count(file.as_deref(), limit, threads),
+        Command::Rate { file } => for_each(file.as_deref(), |board| {
+            let rating = rate(&board)?;
+            let hardest = rating.hardest.map_or("-", |technique| technique.name());
+            Ok(format!(
+                "{:.1}\t{:?}\t{hardest}",
+                rating.se,
+                rating.difficulty()
+            ))
+        }),
+        Command::Validate { file } => for_each(file.as_deref(), |board| {
+            let game: Vec<u8> = board.cells.iter().map(|cell| cell.get_value()).collect();
+            check_game(&game)?;
+            match Dlx.count_solutions(&board, 2)? {
+                0 => Err(SudokuError::NoSolution),
+                1 => Ok("valid".to_string()),
+                _ => Err(SudokuError::MultipleSolutions),
+            }
+        }),
+        Command::Convert { file, format } => convert(file.as_deref(), format),
+        Command::Collection {
+            file,
+            table,
+            no_rate,
+            threads,
+        } => collection(file.as_deref(), table, !no_rate, threads),
+        Command::Bench {
+            count,
+            seed,
+            difficulty,
+            threads,
+        } => bench(count, batch_options(seed, difficulty, threads)),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        // the reader of a pipeline has seen enough
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("newgame: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn batch_options(
+    seed: Option<u64>,
+    difficulty: Option<Level>,
+    threads: Option<usize>,
+) -> BatchOptions {
+    let mut options = BatchOptions::default();
+    if let Some(seed) = seed {
+        options.seed = seed;
+    }
+    if let Some(threads) = threads {
+        options.threads = threads;
+    }
+    options.difficulty = difficulty.map(Difficulty::from);
+    options
+}
+
+// the text of a file, or of stdin without one
+fn read_input(file: Option<&Path>) -> io::Result<String> {
+    match file {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+// the puzzles of a file, or of stdin without one, with where each is: its line, or its number
+// in a file of another program
+fn read_puzzles(file: Option<&Path>) -> io::Result<Vec<(String, Result<Board, SudokuError>)>> {
+    let input = read_input(file)?;
+
+    // a file of another program holds its puzzles its own way
+    let format = file
+        .and_then(|path| path.extension())
+        .and_then(|extension| Format::from_extension(&extension.to_string_lossy()));
+    if let (Some(path), Some(format)) = (file, format) {
+        return Ok(match format.read(&input) {
+            Ok(boards) => boards
+                .into_iter()
+                .enumerate()
+                .map(|(index, board)| (format!("puzzle {}", index + 1), Ok(board)))
+                .collect(),
+            Err(error) => vec![(path.display().to_string(), Err(error))],
+        });
+    }
+
+    let mut puzzles = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let Some(game) = parse_line(line) else {
+            continue;
+        };
+        let board = game.and_then(|game| {
+            let mut board = Board::new();
+            board.load_game(&game)?;
+            for cell in &mut board.cells {
+                cell.set_readonly(cell.is_fixed());
+            }
+            Ok(board)
+        });
+        puzzles.push((format!("line {}", number + 1), board));
+    }
+    Ok(puzzles)
+}
+
+/**
+ * Write one board in `format`. An OpenSudoku collection is only written once every board is
+ * known, so its boards are kept in `collection` until `finish`.
+ */
+struct Output {
+    format: OutputFormat,
+    collection: Vec<Board>,
+}
+
+impl Output {
+    fn new(format: OutputFormat) -> Self {
+        Output {
+            format,
+            collection: Vec::new(),
+        }
+    }
+
+    fn write(&mut self, board: Board) -> io::Result<()> {
+        let text = match self.format {
+            OutputFormat::Line => board.serialize() + "\n",
+            OutputFormat::Pm => {
+                let mut marks = Board::new();
+                let values: Vec<u8> = board.cells.iter().map(|cell| cell.get_value()).collect();
+                marks.load_candidates(&values);
+                for (mark, cell) in marks.cells.iter_mut().zip(&board.cells) {
+                    if !cell.is_fixed() && cell.get_candidates() != 0 {
+                        mark.restore(cell.backup());
+                    }
+                }
+                pencilmarks::write_grid(&marks)
+            }
+            OutputFormat::Sdk => Format::Sdk.write(&board),
+            OutputFormat::Sdx => Format::Sdx.write(&board),
+            OutputFormat::Ss => Format::Ss.write(&board),
+            OutputFormat::Opensudoku => {
+                self.collection.push(board);
+                return Ok(());
+            }
+        };
+        io::stdout().write_all(text.as_bytes())
+    }
+
+    fn finish(self) -> io::Result<()> {
+        if self.format == OutputFormat::Opensudoku {
+            let text = opensudoku::write_collection("newgame", &self.collection);
+            io::stdout().write_all(text.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+fn generate(count: usize, options: BatchOptions, format: OutputFormat) -> io::Result<bool> {
+    let mut output = Output::new(format);
+    for puzzle in generate_batch(count, options) {
+        let mut board = Board::new();
+        board
+            .load_game(&game_str_to_vec(&puzzle.givens).unwrap())
+            .unwrap();
+        output.write(board)?;
+    }
+    output.finish()?;
+    Ok(true)
+}
+
+// the box shape of a grid side, None after telling why there is none
+fn geometry(size: usize) -> Option<Geometry> {
+    let geometry = Geometry::for_size(size);
+    if geometry.is_none() {
+        eprintln!("newgame: there is no grid of side {size}");
+    }
+    geometry
+}
+
+fn generate_grids(count: usize, seed: Option<u64>, size: usize) -> io::Result<bool> {
+    let Some(geometry) = geometry(size) else {
+        return Ok(false);
+    };
+    let seed = seed.unwrap_or_else(rand::random);
+    for k in 0..count {
+        let mut generator = Generator::from_seed(seed.wrapping_add(k as u64));
+        // the boxes of any side have a solution
+        let solution = generator.generate_grid(&geometry).unwrap();
+        let puzzle = generator.dig_grid(&solution).unwrap();
+        writeln!(io::stdout(), "{}", puzzle.serialize())?;
+    }
+    Ok(true)
+}
+
+fn solve_grids(file: Option<&Path>, size: usize) -> io::Result<bool> {
+    let Some(geometry) = geometry(size) else {
+        return Ok(false);
+    };
+    let mut success = true;
+    for (number, line) in read_input(file)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let solved = Grid::parse(geometry.clone(), line, ParseMode::Strict).and_then(|mut grid| {
+            grid.solve()?;
+            Ok(grid)
+        });
+        match solved {
+            Ok(grid) => writeln!(io::stdout(), "{}", grid.serialize())?,
+            Err(error) => {
+                eprintln!("line {}: {error}", number + 1);
+                success = false;
+            }
+        }
+    }
+    Ok(success)
+}
+
+// run `task` on every puzzle, printing its line or the error of the puzzle, false on any error
+fn for_each(
+    file: Option<&Path>,
+    mut task: impl FnMut(Board) -> Result<String, SudokuError>,
+) -> io::Result<bool> {
+    let mut success = true;
+    for (place, board) in read_puzzles(file)? {
+        match board.and_then(&mut task) {
+            Ok(line) => writeln!(io::stdout(), "{line}")?,
+            Err(error) => {
+                // keep one line per puzzle
+                writeln!(io::stdout(), "error")?;
+                eprintln!("{place}: {error}");
+                success = false;
+            }
+        }
+    }
+    Ok(success)
+}
+
+fn solver(threads: Option<usize>) -> Box<dyn Solver> {
+    match threads {
+        Some(threads) => Box::new(Parallel::new(threads)),
+        None => Box::new(Dlx),
+    }
+}
+
+fn solve(file: Option<&Path>, format: OutputFormat, threads: Option<usize>) -> io::Result<bool> {
+    let solver = solver(threads);
+    let mut output = Output::new(format);
+    let mut success = true;
+    for (place, board) in read_puzzles(file)? {
+        let solved = board.and_then(|mut board| solver.solve(&mut board).map(|_| board));
+        match solved {
+            Ok(board) => output.write(board)?,
+            Err(error) => {
+                eprintln!("{place}: {error}");
+                success = false;
+            }
+        }
+    }
+    output.finish()?;
+    Ok(success)
+}
+
+fn count(file: Option<&Path>, limit: usize, threads: Option<usize>) -> io::Result<bool> {
+    let solver = solver(threads);
+    for_each(file, |board| {
+        Ok(solver.count_solutions(&board, limit)?.to_string())
+    })
+}
+
+fn convert(file: Option<&Path>, format: OutputFormat) -> io::Result<bool> {
+    let mut output = Output::new(format);
+    let mut success = true;
+    for (place, board) in read_puzzles(file)? {
+        match board {
+            Ok(board) => output.write(board)?,
+            Err(error) => {
+                eprintln!("{place}: {error}");
+                success = false;
+            }
+        }
+    }
+    output.finish()?;
+    Ok(success)
+}
+
+fn collection(
+    file: Option<&Path>,
+    table: Table,
+    rate: bool,
+    threads: Option<usize>,
+) -> io::Result<bool> {
+    let mut options = CollectionOptions {
+        rate,
+        ..CollectionOptions::default()
+    };
+    if let Some(threads) = threads {
+        options.threads = threads;
+    }
+    let results = process_collection(&read_input(file)?, &options);
+    let separator = match table {
+        Table::Tsv => '\t',
+        Table::Csv => ',',
+    };
+    io::stdout().write_all(write_results(&results, separator).as_bytes())?;
+    for result in &results {
+        if let Some(error) = &result.error {
+            eprintln!("line {}: {error}", result.line);
+        }
+    }
+    Ok(results.iter().all(|result| result.error.is_none()))
+}
+
+fn bench(count: usize, options: BatchOptions) -> io::Result<bool> {
+    let threads = options.threads;
+    let start = Instant::now();
+    let puzzles: Vec<_> = generate_batch(count, options).collect();
+    let elapsed = start.elapsed();
+
+    let clues: usize = puzzles.iter().map(|puzzle| puzzle.clues).sum();
+    println!(
+        "{} puzzles on {threads} threads in {:.3} s",
+        puzzles.len(),
+        elapsed.as_secs_f64()
+    );
+    if !puzzles.is_empty() {
+        let n = puzzles.len() as f64;
+        println!("{:.3} ms per puzzle", elapsed.as_secs_f64() * 1000.0 / n);
+        println!("{:.1} clues per puzzle", clues as f64 / n);
+    }
+    Ok(true)
+}
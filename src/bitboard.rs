@@ -0,0 +1,482 @@
+use crate::board::Board;
+
+// one bit per cell, bit i for cell i
+const ALL_CELLS: u128 = (1 << 81) - 1;
+
+// rows 0 - 8, columns 9 - 17, blocks 18 - 26
+const UNITS: [u128; 27] = build_units();
+const CELL_UNITS: [[usize; 3]; 81] = build_cell_units();
+const PEERS: [u128; 81] = build_peers();
+
+const fn build_cell_units() -> [[usize; 3]; 81] {
+    let mut result = [[0; 3]; 81];
+    let mut i = 0;
+    while i < 81 {
+        let (row, col) = (i / 9, i % 9);
+        result[i] = [row, 9 + col, 18 + (row / 3) * 3 + col / 3];
+        i += 1;
+    }
+    result
+}
+
+const fn build_units() -> [u128; 27] {
+    let cell_units = build_cell_units();
+    let mut result = [0; 27];
+    let mut i = 0;
+    while i < 81 {
+        let mut k = 0;
+        while k < 3 {
+            result[cell_units[i][k]] |= 1 << i;
+            k += 1;
+        }
+        i += 1;
+    }
+    result
+}
+
+const fn build_peers() -> [u128; 81] {
+    let units = build_units();
+    let cell_units = build_cell_units();
+    let mut result = [0; 81];
+    let mut i = 0;
+    while i < 81 {
+        let [row, col, block] = cell_units[i];
+        result[i] = (units[row] | units[col] | units[block]) & !(1 << i);
+        i += 1;
+    }
+    result
+}
+
+/**
+ * A compact board for searching: for each digit, an 81 bit mask of the cells where it can still
+ * go, and for each unit, a 9 bit mask of the digits already placed in it. Placing a digit or
+ * removing a candidate only revisits the cells and units it touches, and the naked and hidden
+ * singles it creates are placed at once. The whole board is a few hundred bytes, so a search
+ * backtracks by copying it.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BitBoard {
+    candidates: [u128; 9],
+    // the same candidates by cell, bit (c - 1) for candidate c
+    cells: [u16; 81],
+    values: [u8; 81],
+    unsolved: u128,
+    placed: [u16; 27],
+}
+
+impl Default for BitBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BitBoard {
+    // an empty board, every digit is a candidate of every cell
+    pub fn new() -> Self {
+        BitBoard {
+            candidates: [ALL_CELLS; 9],
+            cells: [0x1ff; 81],
+            values: [0; 81],
+            unsolved: ALL_CELLS,
+            placed: [0; 27],
+        }
+    }
+
+    // the board of a game, None if its givens contradict each other or are not digits
+    pub fn from_game(game: &[u8]) -> Option<Self> {
+        let mut board = BitBoard::new();
+        // the singles left by the givens are only looked for once they are all placed
+        for (i, value) in game.iter().enumerate() {
+            if *value == 0 {
+                continue;
+            }
+            if *value > 9 {
+                return None;
+            }
+            let digit = *value as usize - 1;
+            if board.candidates[digit] & (1 << i) == 0 {
+                return None;
+            }
+            board.place(i, digit);
+        }
+        if !board.propagate() {
+            return None;
+        }
+        Some(board)
+    }
+
+    /**
+     * The board of the fixed cells and candidates of a `Board`, None if they leave a cell or a
+     * digit of a unit without any place.
+     */
+    pub fn from_board(board: &Board) -> Option<Self> {
+        let mut result = BitBoard::new();
+        for (i, cell) in board.cells.iter().enumerate() {
+            if cell.is_fixed() {
+                continue;
+            }
+            let candidates = cell.get_candidates();
+            for value in 1..=9 {
+                if candidates & (1 << (value - 1)) == 0 && !result.eliminate(i as u8, value) {
+                    return None;
+                }
+            }
+        }
+        for (i, cell) in board.cells.iter().enumerate() {
+            if cell.is_fixed() && !result.assign(i as u8, cell.get_value()) {
+                return None;
+            }
+        }
+        Some(result)
+    }
+
+    // copy the values and candidates to the cells of a `Board`, keeping their readonly flag
+    pub fn write_to(&self, board: &mut Board) {
+        for i in 0..81 {
+            let value = self.values[i];
+            let candidates = if value > 0 {
+                0
+            } else {
+                self.cell_candidates(i as u8)
+            };
+            board.cells[i].restore((value, candidates));
+        }
+    }
+
+    pub fn to_game(&self) -> Vec<u8> {
+        self.values.to_vec()
+    }
+
+    pub fn value(&self, index: u8) -> u8 {
+        self.values[index as usize]
+    }
+
+    // candidates of a cell as a bit set, bit (c - 1) is set for candidate c
+    pub fn cell_candidates(&self, index: u8) -> u16 {
+        self.cells[index as usize]
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.unsolved == 0
+    }
+
+    // the unsolved cells as a bit mask
+    pub fn unsolved(&self) -> u128 {
+        self.unsolved
+    }
+
+    /**
+     * Place a digit and every single that follows from it. Returns false if the board runs
+     * into a contradiction, the board is then only good to be thrown away.
+     */
+    pub fn assign(&mut self, index: u8, value: u8) -> bool {
+        let i = index as usize;
+        if self.values[i] > 0 {
+            return self.values[i] == value;
+        }
+        let digit = value as usize - 1;
+        let bit = 1u128 << index;
+        if self.candidates[digit] & bit == 0 {
+            return false;
+        }
+
+        let others = self.cells[i] & !(1 << digit);
+        let mut affected = self.place(i, digit);
+
+        // the units that lost the digit somewhere, as a 27 bit mask
+        let mut units = 0u32;
+        let mut peers = affected;
+        while peers != 0 {
+            let peer = peers.trailing_zeros() as usize;
+            peers &= peers - 1;
+            for unit in CELL_UNITS[peer] {
+                units |= 1 << unit;
+            }
+        }
+        while affected != 0 {
+            let peer = affected.trailing_zeros() as u8;
+            affected &= affected - 1;
+            if !self.check_cell(peer) {
+                return false;
+            }
+        }
+        while units != 0 {
+            let unit = units.trailing_zeros() as usize;
+            units &= units - 1;
+            if !self.check_unit(unit, digit) {
+                return false;
+            }
+        }
+        for d in 0..9 {
+            if others & (1 << d) != 0 {
+                for unit in CELL_UNITS[i] {
+                    if !self.check_unit(unit, d) {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /**
+     * Remove a candidate and place every single that follows from it. Returns false if the
+     * board runs into a contradiction.
+     */
+    pub fn eliminate(&mut self, index: u8, value: u8) -> bool {
+        let i = index as usize;
+        if self.values[i] > 0 {
+            return self.values[i] != value;
+        }
+        let digit = value as usize - 1;
+        let bit = 1u128 << index;
+        if self.candidates[digit] & bit == 0 {
+            return true;
+        }
+
+        self.candidates[digit] &= !bit;
+        self.cells[i] &= !(1 << digit);
+        if !self.check_cell(index) {
+            return false;
+        }
+        CELL_UNITS[i]
+            .iter()
+            .all(|unit| self.check_unit(*unit, digit))
+    }
+
+    // set the value of a cell and remove it from its peers, returns the peers that had it
+    fn place(&mut self, i: usize, digit: usize) -> u128 {
+        let bit = 1u128 << i;
+        self.values[i] = digit as u8 + 1;
+        self.unsolved &= !bit;
+        let mut digits = self.cells[i];
+        self.cells[i] = 0;
+        while digits != 0 {
+            self.candidates[digits.trailing_zeros() as usize] &= !bit;
+            digits &= digits - 1;
+        }
+        for unit in CELL_UNITS[i] {
+            self.placed[unit] |= 1 << digit;
+        }
+
+        let affected = self.candidates[digit] & PEERS[i];
+        self.candidates[digit] &= !PEERS[i];
+        let mut peers = affected;
+        while peers != 0 {
+            self.cells[peers.trailing_zeros() as usize] &= !(1 << digit);
+            peers &= peers - 1;
+        }
+        affected
+    }
+
+    // place every single of the board, pass after pass until none is left
+    fn propagate(&mut self) -> bool {
+        loop {
+            let mut progress = false;
+            let mut unsolved = self.unsolved;
+            while unsolved != 0 {
+                let i = unsolved.trailing_zeros() as usize;
+                unsolved &= unsolved - 1;
+                let candidates = self.cells[i];
+                if candidates == 0 {
+                    return false;
+                }
+                if candidates & (candidates - 1) == 0 {
+                    self.place(i, candidates.trailing_zeros() as usize);
+                    progress = true;
+                }
+            }
+            for digit in 0..9 {
+                for (unit, unit_cells) in UNITS.iter().enumerate() {
+                    if self.placed[unit] & (1 << digit) != 0 {
+                        continue;
+                    }
+                    let cells = self.candidates[digit] & unit_cells;
+                    if cells == 0 {
+                        return false;
+                    }
+                    if cells & (cells - 1) == 0 {
+                        self.place(cells.trailing_zeros() as usize, digit);
+                        progress = true;
+                    }
+                }
+            }
+            if !progress {
+                return true;
+            }
+        }
+    }
+
+    // a cell left with a single candidate gets it
+    fn check_cell(&mut self, index: u8) -> bool {
+        if self.values[index as usize] > 0 {
+            return true;
+        }
+        let candidates = self.cell_candidates(index);
+        match candidates.count_ones() {
+            0 => false,
+            1 => self.assign(index, candidates.trailing_zeros() as u8 + 1),
+            _ => true,
+        }
+    }
+
+    // a digit left with a single place in a unit goes there
+    fn check_unit(&mut self, unit: usize, digit: usize) -> bool {
+        if self.placed[unit] & (1 << digit) != 0 {
+            return true;
+        }
+        let cells = self.candidates[digit] & UNITS[unit];
+        match cells.count_ones() {
+            0 => false,
+            1 => self.assign(cells.trailing_zeros() as u8, digit as u8 + 1),
+            _ => true,
+        }
+    }
+
+    // the unsolved cell with the fewest candidates and its candidates, None if solved
+    pub fn next_cell(&self) -> Option<(u8, u16)> {
+        let mut best: Option<(u8, u16)> = None;
+        let mut min = 10;
+        let mut unsolved = self.unsolved;
+        while unsolved != 0 {
+            let index = unsolved.trailing_zeros() as u8;
+            unsolved &= unsolved - 1;
+            let candidates = self.cell_candidates(index);
+            let count = candidates.count_ones();
+            if count < min {
+                min = count;
+                best = Some((index, candidates));
+                if count <= 2 {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    /**
+     * Call `found` with every solution until `limit` of them are found, and return how many
+     * were found. The board itself is left untouched.
+     */
+    pub fn search(&self, limit: usize, found: &mut dyn FnMut(&BitBoard)) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+        let (index, candidates) = match self.next_cell() {
+            Some(cell) => cell,
+            None => {
+                found(self);
+                return 1;
+            }
+        };
+
+        let mut count = 0;
+        for value in 1..=9 {
+            if candidates & (1 << (value - 1)) == 0 {
+                continue;
+            }
+            let mut next = *self;
+            if next.assign(index, value) {
+                count += next.search(limit - count, found);
+                if count >= limit {
+                    break;
+                }
+            }
+        }
+        count
+    }
+
+    pub fn solve(&mut self) -> bool {
+        let mut solution = None;
+        self.search(1, &mut |board| solution = Some(*board));
+        match solution {
+            Some(board) => {
+                *self = board;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // like `Board::count_solutions`, a result equal to `limit` means at least `limit` solutions
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.search(limit, &mut |_| {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitBoard, PEERS, UNITS};
+    use crate::board::{game_str_to_vec, Board};
+
+    #[test]
+    fn test_tables() {
+        assert_eq!(UNITS[0], 0x1ff);
+        assert_eq!(UNITS[9].count_ones(), 9);
+        assert_eq!(UNITS[18], 0x1c0e07);
+        for peers in PEERS {
+            assert_eq!(peers.count_ones(), 20);
+        }
+    }
+
+    #[test]
+    fn test_assign_propagates() {
+        let game =
+            "62851943757942361841378652985793214636214789519486527378135496223569178494627835.";
+        let mut board = BitBoard::from_game(&game_str_to_vec(game).unwrap()).unwrap();
+        // the last cell is a naked single
+        assert!(board.is_solved());
+        assert_eq!(board.value(80), 1);
+
+        board = BitBoard::new();
+        for value in 1..=8 {
+            assert!(board.assign(value - 1, value));
+        }
+        assert_eq!(board.value(8), 9);
+        assert!(!board.assign(9, 1));
+    }
+
+    #[test]
+    fn test_eliminate() {
+        let mut board = BitBoard::new();
+        for value in 1..=8 {
+            assert!(board.eliminate(0, value));
+        }
+        assert_eq!(board.value(0), 9);
+        assert!(!board.eliminate(0, 9));
+        assert_eq!(board.cell_candidates(1), 0xff);
+    }
+
+    #[test]
+    fn test_from_board() {
+        let game =
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let game_vec = game_str_to_vec(game).unwrap();
+        let mut board = Board::new();
+        board.init(&game_vec).unwrap();
+        let bits = BitBoard::from_board(&board).unwrap();
+        assert_eq!(Some(bits), BitBoard::from_game(&game_vec));
+
+        let mut copy = Board::new();
+        bits.write_to(&mut copy);
+        assert_eq!(copy.backup(), board.backup());
+
+        let mut conflict = Board::new();
+        conflict.load_game(&[1; 81]).unwrap();
+        assert_eq!(BitBoard::from_board(&conflict), None);
+    }
+
+    #[test]
+    fn test_search() {
+        let game =
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let mut board = BitBoard::from_game(&game_str_to_vec(game).unwrap()).unwrap();
+        assert_eq!(board.count_solutions(2), 1);
+        assert!(board.solve());
+        assert!(Board::is_valid_game(&board.to_game()));
+        assert!(!board.to_game().contains(&0));
+
+        assert_eq!(BitBoard::new().count_solutions(10), 10);
+        assert_eq!(BitBoard::new().count_solutions(0), 0);
+    }
+}
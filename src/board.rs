@@ -1,19 +1,14 @@
-use std::sync::{Arc, Mutex};
-use std::{fmt, thread};
-extern crate num_cpus;
+use crate::bitboard::BitBoard;
 use crate::cell::{one_hot, Cell};
+use crate::constraint::{self, Constraint};
+use crate::error::SudokuError;
+use crate::parser::{parse_game, ParseMode};
+use crate::solver::Parallel;
+use crate::variant::Variant;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 
-type CellState = (u8, u16);
-
-type BoardState = Vec<CellState>;
-
-struct Strategy {
-    state: BoardState,
-    index: u8,
-    value: u8,
-}
-
-type Unit = [u8; 9];
+pub(crate) type Unit = [u8; 9];
 
 const ROWS: [Unit; 9] = [
     [0, 1, 2, 3, 4, 5, 6, 7, 8],
@@ -51,6 +46,20 @@ const BLOCKS: [Unit; 9] = [
     [60, 61, 62, 69, 70, 71, 78, 79, 80],
 ];
 
+// the main diagonal from r1c1, then the anti-diagonal from r1c9
+const DIAGONALS: [Unit; 2] = [
+    [0, 10, 20, 30, 40, 50, 60, 70, 80],
+    [8, 16, 24, 32, 40, 48, 56, 64, 72],
+];
+
+// the windows of Windoku, with their top left cells at r2c2, r2c6, r6c2 and r6c6
+const WINDOWS: [Unit; 4] = [
+    [10, 11, 12, 19, 20, 21, 28, 29, 30],
+    [14, 15, 16, 23, 24, 25, 32, 33, 34],
+    [46, 47, 48, 55, 56, 57, 64, 65, 66],
+    [50, 51, 52, 59, 60, 61, 68, 69, 70],
+];
+
 fn get_row_unit(row: u8) -> &'static Unit {
     &ROWS[row as usize]
 }
@@ -71,52 +80,193 @@ fn get_block_unit_by_pos(row: u8, col: u8) -> &'static Unit {
     get_block_unit_by_index(block_id)
 }
 
-pub fn game_str_to_vec(game: &str) -> Result<Vec<u8>, &str> {
+// the row, column and block containing a cell
+pub(crate) fn get_cell_units(index: u8) -> [&'static Unit; 3] {
+    let row = index / 9;
+    let col = index % 9;
+    [
+        get_row_unit(row),
+        get_col_unit(col),
+        get_block_unit_by_pos(row, col),
+    ]
+}
+
+/**
+ * Names a unit of the board, numbered from 0 like the cells. Diagonals and windows are only
+ * units of the variants that add them, see `Variant`.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum UnitId {
+    Row(u8),
+    Column(u8),
+    Block(u8),
+    Diagonal(u8),
+    Window(u8),
+}
+
+impl UnitId {
+    // all 27 classic units, rows first, then columns and blocks
+    pub fn all() -> impl Iterator<Item = UnitId> {
+        (0..9)
+            .map(UnitId::Row)
+            .chain((0..9).map(UnitId::Column))
+            .chain((0..9).map(UnitId::Block))
+    }
+
+    pub fn cells(&self) -> &'static [u8; 9] {
+        match self {
+            UnitId::Row(i) => get_row_unit(*i),
+            UnitId::Column(i) => get_col_unit(*i),
+            UnitId::Block(i) => get_block_unit_by_index(*i),
+            UnitId::Diagonal(i) => &DIAGONALS[*i as usize],
+            UnitId::Window(i) => &WINDOWS[*i as usize],
+        }
+    }
+}
+
+impl fmt::Display for UnitId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnitId::Row(i) => write!(f, "row {}", i + 1),
+            UnitId::Column(i) => write!(f, "column {}", i + 1),
+            UnitId::Block(i) => write!(f, "block {}", i + 1),
+            UnitId::Diagonal(i) => write!(f, "diagonal {}", i + 1),
+            UnitId::Window(i) => write!(f, "window {}", i + 1),
+        }
+    }
+}
+
+// a cell named by its row and column counted from 1, like r4c8
+pub fn cell_name(index: u8) -> String {
+    format!("r{}c{}", index / 9 + 1, index % 9 + 1)
+}
+
+// a game of exactly 81 digits and blanks, see `parser` for the other layouts
+pub fn game_str_to_vec(game: &str) -> Result<Vec<u8>, SudokuError> {
+    parse_game(game, ParseMode::Strict)
+}
+
+// 81 cells, each blank or holding a digit from 1 to 9
+pub(crate) fn check_cells(game: &[u8]) -> Result<(), SudokuError> {
     if game.len() != 81 {
-        return Err("not valid");
+        return Err(SudokuError::InvalidLength {
+            expected: 81,
+            found: game.len(),
+        });
+    }
+    match game.iter().position(|&value| value > 9) {
+        Some(cell) => Err(SudokuError::InvalidDigit {
+            cell: cell as u8,
+            digit: game[cell],
+        }),
+        None => Ok(()),
     }
-    let mut result: Vec<u8> = Vec::with_capacity(81);
+}
+
+/**
+ * Check that a game has 81 cells holding digits from 0 to 9 and no digit twice in a unit, the
+ * first repeated digit is reported at its second cell.
+ */
+pub fn check_game(game: &[u8]) -> Result<(), SudokuError> {
+    check_game_with(game, &[])
+}
 
-    for i in 0..81 {
-        if let Some(value) = game.chars().nth(i).unwrap().to_digit(10) {
-            result.push(value as u8);
-        } else {
-            result.push(0);
+// like `check_game`, with the extra units of the variants checked after the classic ones
+pub fn check_game_with(game: &[u8], variants: &[Variant]) -> Result<(), SudokuError> {
+    check_cells(game)?;
+    let extra = variants.iter().flat_map(|variant| variant.units());
+    for unit in UnitId::all().chain(extra) {
+        let mut seen: u16 = 0;
+        for cell in unit.cells() {
+            let digit = game[*cell as usize];
+            if digit == 0 {
+                continue;
+            }
+            if seen & one_hot(digit) != 0 {
+                return Err(SudokuError::Conflict {
+                    cell: *cell,
+                    digit,
+                    unit,
+                });
+            }
+            seen |= one_hot(digit);
         }
     }
-    Ok(result)
+    Ok(())
 }
 
+/**
+ * The cells of a game with their values and candidates, as shown and edited by the UI and the
+ * logic solver. Searches such as `solve` and `count_solutions` run on a `BitBoard` copy, and
+ * respect the extra rules added with `add_constraint`.
+ */
+#[derive(Clone)]
 pub struct Board {
     pub cells: [Cell; 81],
+    // every rule the searches respect, those of the variants and those of `add_constraint`
+    constraints: Vec<Arc<dyn Constraint>>,
+    variants: Vec<Variant>,
+    // the rules added with `add_constraint`, which a variant does not stand for
+    custom: Vec<Arc<dyn Constraint>>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Board {
     pub fn new() -> Self {
         Board {
             cells: [Cell::new(); 81],
+            constraints: Vec::new(),
+            variants: Vec::new(),
+            custom: Vec::new(),
         }
     }
 
-    pub fn is_valid_game(game: &Vec<u8>) -> bool {
-        let mut board = Board::new();
-        board.load_game(&game);
+    pub fn is_valid_game(game: &[u8]) -> bool {
+        check_game(game).is_ok()
+    }
 
-        for i in 0..9 {
-            let row = get_row_unit(i);
-            if !board.is_unit_compatible(row) {
-                return false;
-            }
-            let col = get_col_unit(i);
-            if !board.is_unit_compatible(col) {
-                return false;
-            }
-            let block = get_block_unit_by_index(i);
-            if !board.is_unit_compatible(block) {
-                return false;
-            }
+    pub fn is_valid_variant_game(game: &[u8], variants: &[Variant]) -> bool {
+        check_game_with(game, variants).is_ok()
+    }
+
+    /**
+     * Add a rule that every search of the board respects from now on. Resetting or loading a
+     * game keeps the rules.
+     */
+    pub fn add_constraint(&mut self, constraint: Arc<dyn Constraint>) {
+        self.custom.push(Arc::clone(&constraint));
+        self.constraints.push(constraint);
+    }
+
+    pub fn constraints(&self) -> &[Arc<dyn Constraint>] {
+        &self.constraints
+    }
+
+    /**
+     * Play a variant: its extra units become rules of the board, and conflicts in them are
+     * reported like those of the classic units. Adding a variant twice changes nothing.
+     */
+    pub fn add_variant(&mut self, variant: Variant) {
+        if !self.variants.contains(&variant) {
+            self.variants.push(variant);
+            self.constraints.extend(variant.constraints());
         }
-        true
+    }
+
+    pub fn variants(&self) -> &[Variant] {
+        &self.variants
+    }
+
+    // like `check_game` on the fixed cells, with the extra rules of the board checked too
+    pub fn check(&self) -> Result<(), SudokuError> {
+        let game: Vec<u8> = self.cells.iter().map(|cell| cell.get_value()).collect();
+        check_game_with(&game, &self.variants)?;
+        constraint::check(&game, &self.constraints)
     }
 
     // reset all cells value
@@ -136,10 +286,8 @@ impl Board {
      * A game is a string of length 81, each character maps to a cell. An example:
      * 4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......
      */
-    pub fn load_game(&mut self, game: &Vec<u8>) {
-        if game.len() != 81 {
-            return;
-        }
+    pub fn load_game(&mut self, game: &[u8]) -> Result<(), SudokuError> {
+        check_cells(game)?;
         self.reset();
         for (i, value) in game.iter().enumerate() {
             if *value > 0 {
@@ -147,28 +295,56 @@ impl Board {
                 cell.set_value(*value);
             }
         }
+        Ok(())
     }
 
-    // fill candidates, then load a new game and update candidates
-    pub fn init(&mut self, game: &Vec<u8>) -> Result<bool, bool> {
-        if game.len() != 81 {
-            return Err(false);
-        }
+    /**
+     * Fill candidates, then load a new game and update candidates. A game whose candidates
+     * run out for some cell has no solution.
+     */
+    pub fn init(&mut self, game: &[u8]) -> Result<(), SudokuError> {
+        check_game_with(game, &self.variants)?;
+        constraint::check(game, &self.constraints)?;
         self.reset();
 
-        for cell in &mut self.cells {
-            cell.fill_candidates();
+        let mut bits = BitBoard::from_game(game).ok_or(SudokuError::NoSolution)?;
+        if !constraint::propagate(&mut bits, &self.constraints) {
+            return Err(SudokuError::NoSolution);
         }
+        bits.write_to(self);
+        Ok(())
+    }
 
+    /**
+     * Load a game and fill the candidates of its empty cells with every digit its units still
+     * allow. No further deduction is made, see `init` for that.
+     */
+    pub fn load_candidates(&mut self, game: &[u8]) {
+        self.reset();
+        self.fill_candidates();
         for (i, value) in game.iter().enumerate() {
             if *value > 0 {
-                if !self.assign_cell(i as u8, *value) {
-                    return Err(false);
-                }
+                self.place(i as u8, *value);
             }
         }
+    }
 
-        Ok(true)
+    /**
+     * The puzzle of a game being played: the readonly cells, or every fixed cell when none is
+     * readonly.
+     */
+    pub fn givens(&self) -> Vec<u8> {
+        let has_readonly = self.cells.iter().any(|cell| cell.is_readonly());
+        self.cells
+            .iter()
+            .map(|cell| {
+                if cell.is_readonly() || !has_readonly {
+                    cell.get_value()
+                } else {
+                    0
+                }
+            })
+            .collect()
     }
 
     /**
@@ -183,11 +359,9 @@ impl Board {
         result
     }
 
-    pub fn restore(&mut self, data: &Vec<(u8, u16)>) {
-        for i in 0..81 {
-            let cell = &mut self.cells[i];
-            let value = data[i];
-            cell.restore(value);
+    pub fn restore(&mut self, data: &[(u8, u16)]) {
+        for (cell, value) in self.cells.iter_mut().zip(data) {
+            cell.restore(*value);
         }
     }
 
@@ -232,93 +406,67 @@ impl Board {
                 return false;
             }
         }
-        return true;
+        // 3. and so are the extra rules
+        let game: Vec<u8> = self.cells.iter().map(|cell| cell.get_value()).collect();
+        constraint::check(&game, &self.constraints).is_ok()
     }
 
     /**
      * try to assign a new value to a cell, check validity during the process.
+     * The singles that follow are placed too, see `BitBoard::assign`. The board is left
+     * untouched on error.
      */
-    pub fn assign_cell(&mut self, index: u8, value: u8) -> bool {
-        let cell = &mut self.cells[index as usize];
-        let row = index / 9;
-        let col = index % 9;
-
-        cell.set_value(value);
-
-        let row_unit = get_row_unit(row);
-        if !self.eliminate_unit_candidate(row_unit, value) {
-            return false;
+    pub fn assign_cell(&mut self, index: u8, value: u8) -> Result<(), SudokuError> {
+        if index >= 81 {
+            return Err(SudokuError::InvalidCell { cell: index });
         }
-
-        let col_unit = get_col_unit(col);
-        if !self.eliminate_unit_candidate(col_unit, value) {
-            return false;
-        }
-
-        let block_unit = get_block_unit_by_pos(row, col);
-        if !self.eliminate_unit_candidate(block_unit, value) {
-            return false;
+        if !(1..=9).contains(&value) {
+            return Err(SudokuError::InvalidDigit {
+                cell: index,
+                digit: value,
+            });
         }
-        true
-    }
-
-    // remove the candidate from specified units
-    fn eliminate_unit_candidate(&mut self, unit: &Unit, candidate: u8) -> bool {
-        for index in unit {
-            let cell = &mut self.cells[*index as usize];
-
-            if cell.is_fixed() {
+        let extra = self.variants.iter().flat_map(|variant| variant.units());
+        for unit in UnitId::all().chain(extra) {
+            let cells = unit.cells();
+            if !cells.contains(&index) {
                 continue;
             }
-
-            if let Err(_) = cell.eliminate_candidate(candidate) {
-                return false;
-            }
-            // only has one candidate after removing
-            let fixed = cell.is_fixed();
-            if fixed {
-                let value = cell.get_value();
-                if !self.assign_cell(*index, value) {
-                    return false;
+            for peer in cells {
+                let cell = &self.cells[*peer as usize];
+                if *peer != index && cell.is_fixed() && cell.get_value() == value {
+                    return Err(SudokuError::Conflict {
+                        cell: index,
+                        digit: value,
+                        unit,
+                    });
                 }
             }
-            if !self.check_unit_content(unit) {
-                return false;
-            }
-            if !self.is_unit_compatible(unit) {
-                return false;
-            }
         }
-        true
+
+        let mut bits = self.bits().ok_or(SudokuError::NoSolution)?;
+        if !bits.assign(index, value) || !constraint::propagate(&mut bits, &self.constraints) {
+            return Err(SudokuError::NoSolution);
+        }
+        bits.write_to(self);
+        Ok(())
     }
 
     /**
-     * If a candidate is only contained in one cell of a unit, then it must be the value of that cell.
+     * Set the value of a cell and remove it from the candidates of the cell's units, without any
+     * further deduction. Unlike `assign_cell`, singles created by the placement are left for the
+     * caller to find, which is what a step-by-step solver needs.
      */
-    fn check_unit_content(&mut self, unit: &Unit) -> bool {
-        for candidate in 1..=9 {
-            let mut fixed = false;
-            let mut cell_has_it: Option<u8> = None;
-            let mut num_cells = 0;
-
-            for index in unit {
-                let cell = &self.cells[*index as usize];
-                if cell.is_fixed() && cell.get_value() == candidate {
-                    fixed = true;
-                    break;
-                } else if cell.has_candidate(candidate).0 {
-                    num_cells += 1;
-                    cell_has_it = Some(*index);
-                }
-            }
-
-            if !fixed && num_cells == 1 {
-                if !self.assign_cell(cell_has_it.unwrap(), candidate) {
-                    return false;
+    pub fn place(&mut self, index: u8, value: u8) {
+        self.cells[index as usize].restore((value, 0));
+        for unit in get_cell_units(index) {
+            for peer in unit {
+                let cell = &mut self.cells[*peer as usize];
+                if !cell.is_fixed() {
+                    cell.remove_candidate(value);
                 }
             }
         }
-        true
     }
 
     // find the cell that has minimum candidates
@@ -340,150 +488,60 @@ impl Board {
         let cell = &self.cells[index as usize];
         let candidates = cell.collect_candidates();
 
-        return (index, candidates);
+        (index, candidates)
     }
 
     /**
      * try to solve current game
      */
-    pub fn solve(&mut self) -> bool {
-        if self.is_solved() {
-            return true;
-        }
-
-        let (index, candidates) = self.next_candidate_cell();
-        let board_state = self.backup();
-
-        // let row = index / 9 + 1;
-        // let col = index % 9 + 1;
-        // let label = cell.candidates_str();
-        // println!("Cell of row {} col {} has candidates: {}", row, col, label);
-
-        for candidate in candidates {
-            // println!("{}", self);
-            // println!("assign {} to row {} col {}", candidate, row, col);
-            if self.assign_cell(index, candidate) {
-                // assign succeed, continue to solve the game
-                if self.solve() {
-                    return true;
-                }
-            }
-            // current candidate failed
-            // println!("row {} col {} can not be {}, try another one\n", row, col, candidate);
-            // restore the board and test next candidate
-            self.restore(&board_state);
-        }
-
-        false
-    }
-
-    // tasks: [(board_state, index, value)]
-    // thread extract task to execute until solved
-    pub fn solve_concurrent(&mut self) -> bool {
-        // create send/receiver vars
-        // to move data through channel
-        // thread-safe and lockable
-        let cores = num_cpus::get();
-
-        let (index, candidates) = self.next_candidate_cell();
-        let board_state = self.backup();
-        let mut init_strategy: Vec<Strategy> = Vec::new();
-        for candidate in candidates {
-            init_strategy.push(Strategy {
-                index,
-                state: board_state.clone(),
-                value: candidate,
-            });
-        }
-
-        let solved = Arc::new(Mutex::new(false));
-        let strategies = Arc::new(Mutex::new(init_strategy));
-
-        // more than one handle
-        // store them in a vec for convenience
-        let mut handles = vec![];
-        // println!("num of cores: {}", cores);
-
-        for i in 0..cores {
-            // clone the transmitter
-            let solved = Arc::clone(&solved);
-            let strategies = Arc::clone(&strategies);
-            let thread_id = i;
-
-            // create the thread
-            let handle = thread::spawn(move || {
-                loop {
-                    // lock the value
-                    {
-                        let finished = solved.lock().unwrap();
-
-                        if *finished {
-                            break;
-                        }
-                    }
-
-                    let mut next_strategy: Option<Strategy> = None;
-                    {
-                        let mut s_pool = strategies.lock().unwrap();
-                        next_strategy = s_pool.pop();
-                    }
-
-                    if let Some(s) = next_strategy {
-                        let mut next_board = Board::new();
-                        next_board.restore(&s.state);
-
-                        // println!("thread {} assign cell {} with value {}", thread_id, s.index, s.value);
-                        if next_board.assign_cell(s.index, s.value) {
-                            if next_board.is_solved() {
-                                let mut s_pool = strategies.lock().unwrap();
-                                let mut finished = solved.lock().unwrap();
-                                // println!("solved in thread {}", thread_id);
-                                *finished = true;
-                                s_pool.clear();
-                                s_pool.push(Strategy {
-                                    index: 0,
-                                    state: next_board.backup(),
-                                    value: 0,
-                                });
-                            } else {
-                                let finished = solved.lock().unwrap();
-                                if !*finished {
-                                    let mut s_pool = strategies.lock().unwrap();
-                                    let (index, candidates) = next_board.next_candidate_cell();
-                                    let board_state = next_board.backup();
-                                    // println!("thread {} add cell {} with candidate {:?}", thread_id, index, candidates);
-                                    for candidate in candidates {
-                                        s_pool.push(Strategy {
-                                            index,
-                                            state: board_state.clone(),
-                                            value: candidate,
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            });
+    pub fn solve(&mut self) -> Result<(), SudokuError> {
+        let bits = self.bits().ok_or(SudokuError::NoSolution)?;
+        let mut solution = None;
+        constraint::search(&bits, &self.constraints, 1, &mut |board| {
+            solution = Some(*board)
+        });
+        solution.ok_or(SudokuError::NoSolution)?.write_to(self);
+        Ok(())
+    }
 
-            // push the handle into the handles
-            // vector so we can join them later
-            handles.push(handle);
+    /**
+     * Like `solve`, with the search spread over one thread per cpu, see `solver::Parallel`.
+     * A board with extra rules is solved on a single thread.
+     */
+    pub fn solve_concurrent(&mut self) -> Result<(), SudokuError> {
+        if !self.constraints.is_empty() {
+            return self.solve();
         }
-
-        // join the handles in the vector
-        for i in handles {
-            i.join().unwrap();
+        let bits = BitBoard::from_board(self).ok_or(SudokuError::NoSolution)?;
+        let solution = Mutex::new(None);
+        Parallel::default().search(&bits, 1, &|board| {
+            *solution.lock().unwrap() = Some(*board);
+        })?;
+        match solution.into_inner().unwrap() {
+            Some(board) => {
+                board.write_to(self);
+                Ok(())
+            }
+            None => Err(SudokuError::NoSolution),
         }
+    }
 
-        // all thread finished
-        let mut s_pool = strategies.lock().unwrap();
-        if let Some(s) = s_pool.pop() {
-            self.reset();
-            self.restore(&s.state);
+    /**
+     * Count the solutions of current game by an exhaustive search, the board is left untouched.
+     * The search stops once `limit` solutions are found, so a result equal to `limit` means
+     * there are at least `limit` solutions. Candidates must be filled, see `init`.
+     */
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        match self.bits() {
+            Some(bits) => constraint::search(&bits, &self.constraints, limit, &mut |_| {}),
+            None => 0,
         }
-        let finished = solved.lock().unwrap();
-        *finished
+    }
+
+    // the board to search, with its rules propagated, None on a contradiction
+    fn bits(&self) -> Option<BitBoard> {
+        let mut bits = BitBoard::from_board(self)?;
+        constraint::propagate(&mut bits, &self.constraints).then_some(bits)
     }
 
     pub fn serialize(&self) -> String {
@@ -495,6 +553,62 @@ impl Board {
     }
 }
 
+// serde stops at arrays of 32 elements, a board is the sequence of its cells and its variants
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct BoardRef<'a> {
+    cells: &'a [Cell],
+    variants: &'a [Variant],
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BoardData {
+    cells: Vec<Cell>,
+    #[serde(default)]
+    variants: Vec<Variant>,
+}
+
+/**
+ * The rules of a board are kept as its variants, which are added again when it is read back.
+ * A rule added with `add_constraint` cannot be written, and fails the serialization.
+ */
+#[cfg(feature = "serde")]
+impl serde::Serialize for Board {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !self.custom.is_empty() {
+            return Err(serde::ser::Error::custom(
+                "the rules added with add_constraint cannot be serialized",
+            ));
+        }
+        BoardRef {
+            cells: &self.cells,
+            variants: &self.variants,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Board {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = BoardData::deserialize(deserializer)?;
+        let values: Vec<u8> = data.cells.iter().map(|cell| cell.get_value()).collect();
+        check_cells(&values).map_err(serde::de::Error::custom)?;
+        let cells: [Cell; 81] = data.cells.try_into().map_err(|cells: Vec<Cell>| {
+            serde::de::Error::invalid_length(cells.len(), &"81 cells")
+        })?;
+        let mut board = Board {
+            cells,
+            ..Board::new()
+        };
+        for variant in data.variants {
+            board.add_variant(variant);
+        }
+        Ok(board)
+    }
+}
+
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let groups = [[1, 2, 3], [4, 5, 6], [7, 8, 9]];
@@ -503,27 +617,21 @@ impl fmt::Display for Board {
             "+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++"
         )?;
 
-        for i in 0..9 {
-            let row = &ROWS[i];
-
-            for j in 0..3 {
-                let group = &groups[j];
-
-                for k in 0..9 {
-                    let idx = row[k] as usize;
-                    let cell: &Cell = &self.cells[idx];
+        for (i, row) in ROWS.iter().enumerate() {
+            for group in &groups {
+                for (k, &idx) in row.iter().enumerate() {
+                    let cell: &Cell = &self.cells[idx as usize];
                     if k % 3 == 0 {
                         write!(f, "┼ ")?;
                     } else {
                         write!(f, "⏐ ")?;
                     }
 
-                    for n in 0..3 {
-                        if cell.is_fixed() && group[n] == 5 {
+                    for &v in group {
+                        if cell.is_fixed() && v == 5 {
                             let v = cell.get_value();
                             write!(f, "{v} ")?;
-                        } else if cell.has_candidate(group[n]).0 {
-                            let v = group[n];
+                        } else if cell.has_candidate(v).0 {
                             write!(f, "{v} ")?;
                         } else {
                             write!(f, "  ")?;
@@ -545,13 +653,21 @@ impl fmt::Display for Board {
             }
         }
 
-        return Ok(());
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{game_str_to_vec, get_block_unit_by_pos, get_col_unit, get_row_unit, Board};
+    use super::{
+        cell_name, check_game, game_str_to_vec, get_block_unit_by_pos, get_col_unit, get_row_unit,
+        Board, UnitId,
+    };
+    use crate::bitboard::BitBoard;
+    use crate::constraint::AllDifferent;
+    use crate::error::SudokuError;
+    use crate::variant::Variant;
+    use std::sync::Arc;
     const GAME: &str =
         "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
 
@@ -564,11 +680,104 @@ mod tests {
         assert_eq!(v[74], 4);
     }
 
+    #[test]
+    fn test_game_errors() {
+        assert_eq!(
+            game_str_to_vec("123"),
+            Err(SudokuError::InvalidLength {
+                expected: 81,
+                found: 3
+            })
+        );
+        let game = GAME.replacen('8', "x", 1);
+        assert_eq!(
+            game_str_to_vec(&game),
+            Err(SudokuError::InvalidCharacter {
+                position: 6,
+                char: 'x'
+            })
+        );
+
+        // the 4 of r1c1 again in r2c1
+        let mut v = game_str_to_vec(GAME).unwrap();
+        v[9] = 4;
+        let conflict = SudokuError::Conflict {
+            cell: 9,
+            digit: 4,
+            unit: UnitId::Column(0),
+        };
+        assert_eq!(check_game(&v), Err(conflict.clone()));
+        assert!(!Board::is_valid_game(&v));
+        let mut b = Board::new();
+        assert_eq!(b.init(&v), Err(conflict));
+        assert_eq!(
+            b.load_game(&v[..80]),
+            Err(SudokuError::InvalidLength {
+                expected: 81,
+                found: 80
+            })
+        );
+
+        // r1c2 can only be 1, which is already in row 1
+        let mut v = vec![0; 81];
+        v[..9].copy_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        for (i, digit) in [2, 3, 4, 5, 6, 7, 8, 9].iter().enumerate() {
+            v[(i + 1) * 9 + 1] = *digit;
+        }
+        assert_eq!(b.init(&v), Err(SudokuError::NoSolution));
+    }
+
+    #[test]
+    fn test_invalid_digit() {
+        for digit in [10, 12, 17, 255] {
+            let mut v = game_str_to_vec(GAME).unwrap();
+            v[1] = digit;
+            let error = Err(SudokuError::InvalidDigit { cell: 1, digit });
+            assert_eq!(check_game(&v), error);
+            let mut b = Board::new();
+            assert_eq!(b.init(&v), error);
+            assert_eq!(b.load_game(&v), error);
+            assert!(BitBoard::from_game(&v).is_none());
+        }
+    }
+
+    #[test]
+    fn is_assign_cell_works() {
+        let v = game_str_to_vec(GAME).unwrap();
+        let mut b = Board::new();
+        b.init(&v).unwrap();
+        let s = b.serialize();
+        assert_eq!(
+            b.assign_cell(1, 8),
+            Err(SudokuError::Conflict {
+                cell: 1,
+                digit: 8,
+                unit: UnitId::Row(0)
+            })
+        );
+        assert_eq!(b.serialize(), s);
+        assert_eq!(
+            b.assign_cell(81, 1),
+            Err(SudokuError::InvalidCell { cell: 81 })
+        );
+        assert_eq!(
+            b.assign_cell(1, 0),
+            Err(SudokuError::InvalidDigit { cell: 1, digit: 0 })
+        );
+        assert_eq!(
+            b.assign_cell(1, 10),
+            Err(SudokuError::InvalidDigit { cell: 1, digit: 10 })
+        );
+        assert_eq!(b.serialize(), s);
+        assert_eq!(b.assign_cell(1, 1), Ok(()));
+        assert_eq!(b.cells[1].get_value(), 1);
+    }
+
     #[test]
     fn is_load_work() {
         let v = game_str_to_vec(GAME).unwrap();
         let mut b = Board::new();
-        b.load_game(&v);
+        b.load_game(&v).unwrap();
         assert_eq!(b.cells[0].get_value(), 4);
         assert_eq!(b.cells[6].get_value(), 8);
         assert_eq!(b.cells[74].get_value(), 4);
@@ -579,7 +788,7 @@ mod tests {
     fn is_init_works() {
         let v = game_str_to_vec(GAME).unwrap();
         let mut b = Board::new();
-        assert_eq!(b.init(&v), Ok(true));
+        assert_eq!(b.init(&v), Ok(()));
         println!("{}", b);
     }
 
@@ -587,7 +796,7 @@ mod tests {
     fn is_backup_restore_works() {
         let v = game_str_to_vec(GAME).unwrap();
         let mut b = Board::new();
-        assert_eq!(b.init(&v), Ok(true));
+        assert_eq!(b.init(&v), Ok(()));
         let s1 = b.serialize();
 
         let backup = b.backup();
@@ -604,7 +813,7 @@ mod tests {
     fn is_serialize_works() {
         let v = game_str_to_vec(GAME).unwrap();
         let mut b = Board::new();
-        b.load_game(&v);
+        b.load_game(&v).unwrap();
         assert_eq!(b.serialize(), GAME);
     }
 
@@ -626,25 +835,143 @@ mod tests {
         }
     }
 
+    #[test]
+    fn is_unit_id_works() {
+        assert_eq!(UnitId::all().count(), 27);
+        assert_eq!(UnitId::Row(3).cells(), get_row_unit(3));
+        assert_eq!(UnitId::Block(4).cells()[0], 30);
+        assert_eq!(UnitId::Column(8).to_string(), "column 9");
+        assert_eq!(cell_name(35), "r4c9");
+    }
+
+    #[test]
+    fn is_place_works() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        b.place(40, 5);
+        assert_eq!(b.cells[40].get_value(), 5);
+        assert!(!b.cells[36].has_candidate(5).0);
+        assert!(!b.cells[4].has_candidate(5).0);
+        assert!(!b.cells[30].has_candidate(5).0);
+        assert!(b.cells[0].has_candidate(5).0);
+        // no cascading, other candidates are untouched
+        assert_eq!(b.cells[36].num_candidates(), 8);
+    }
+
     #[test]
     fn is_solve_works() {
         let v = game_str_to_vec(GAME).unwrap();
         let mut b = Board::new();
-        assert_eq!(b.init(&v), Ok(true));
+        assert_eq!(b.init(&v), Ok(()));
         println!("{}", b);
-        let success = b.solve();
-        assert_eq!(success, true);
+        assert_eq!(b.solve(), Ok(()));
         println!("{}", b);
     }
 
+    #[test]
+    fn is_count_solutions_works() {
+        let v = game_str_to_vec(GAME).unwrap();
+        let mut b = Board::new();
+        assert_eq!(b.init(&v), Ok(()));
+        let s = b.serialize();
+        assert_eq!(b.count_solutions(2), 1);
+        // the board is not changed by counting
+        assert_eq!(b.serialize(), s);
+
+        // a solution with the corners of a rectangle removed, the two digits can be swapped
+        let game =
+            "6285194375794236184137865298.793214.3.214789.194865273781354962235691784946278351";
+        let v = game_str_to_vec(game).unwrap();
+        assert_eq!(b.init(&v), Ok(()));
+        assert_eq!(b.count_solutions(10), 2);
+        assert_eq!(b.count_solutions(1), 1);
+        assert_eq!(b.count_solutions(0), 0);
+
+        let empty = vec![0; 81];
+        assert_eq!(b.init(&empty), Ok(()));
+        assert_eq!(b.count_solutions(5), 5);
+    }
+
     #[test]
     fn is_solve_concurrent_works() {
         let v = game_str_to_vec(GAME).unwrap();
         let mut b = Board::new();
-        assert_eq!(b.init(&v), Ok(true));
+        assert_eq!(b.init(&v), Ok(()));
         println!("{}", b);
-        let success = b.solve_concurrent();
-        assert_eq!(success, true);
+        assert_eq!(b.solve_concurrent(), Ok(()));
         println!("{}", b);
     }
+
+    #[test]
+    fn test_constraints() {
+        let diagonal = AllDifferent::new((0..9).map(|i| i * 10).collect()).unwrap();
+        let mut b = Board::new();
+        b.add_constraint(Arc::new(diagonal));
+        let mut game = vec![0; 81];
+        game[0] = 4;
+        b.init(&game).unwrap();
+        assert_eq!(b.constraints().len(), 1);
+        // the diagonal rule removed 4 from r9c9
+        assert!(!b.cells[80].has_candidate(4).0);
+        assert_eq!(b.assign_cell(80, 4).err(), Some(SudokuError::NoSolution));
+        assert_eq!(b.solve(), Ok(()));
+        assert!(b.is_solved());
+        let game: Vec<u8> = b.cells.iter().map(|cell| cell.get_value()).collect();
+        let mut digits: Vec<u8> = (0..9).map(|i| game[i * 10]).collect();
+        digits.sort();
+        assert_eq!(digits, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        let mut game = vec![0; 81];
+        game[0] = 4;
+        game[40] = 4;
+        assert_eq!(
+            b.init(&game),
+            Err(SudokuError::Unsatisfied {
+                constraint: "all different r1c1 r2c2 r3c3 r4c4 r5c5 r6c6 r7c7 r8c8 r9c9".into()
+            })
+        );
+        b.load_game(&game).unwrap();
+        assert!(b.check().is_err());
+        assert!(!b.is_solved());
+    }
+
+    #[test]
+    fn test_variants() {
+        // 5 twice on the anti-diagonal, in r1c9 and r9c1
+        let mut game = vec![0; 81];
+        game[8] = 5;
+        game[72] = 5;
+        assert!(Board::is_valid_game(&game));
+        assert!(Board::is_valid_variant_game(&game, &[Variant::Windoku]));
+        assert!(!Board::is_valid_variant_game(&game, &[Variant::Diagonal]));
+
+        let mut b = Board::new();
+        b.add_variant(Variant::Diagonal);
+        b.add_variant(Variant::Diagonal);
+        assert_eq!(b.variants(), [Variant::Diagonal]);
+        assert_eq!(b.constraints().len(), 2);
+        assert_eq!(
+            b.init(&game),
+            Err(SudokuError::Conflict {
+                cell: 72,
+                digit: 5,
+                unit: UnitId::Diagonal(1)
+            })
+        );
+
+        b.init(&[0; 81]).unwrap();
+        b.assign_cell(0, 7).unwrap();
+        assert_eq!(
+            b.assign_cell(80, 7),
+            Err(SudokuError::Conflict {
+                cell: 80,
+                digit: 7,
+                unit: UnitId::Diagonal(0)
+            })
+        );
+        b.add_variant(Variant::Windoku);
+        assert_eq!(b.solve(), Ok(()));
+        let solution: Vec<u8> = b.cells.iter().map(|cell| cell.get_value()).collect();
+        assert!(Board::is_valid_variant_game(&solution, b.variants()));
+    }
 }
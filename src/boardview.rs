@@ -1,4 +1,3 @@
-use crate::cell::Cell;
 use crate::cellview::{CellMode, CellView};
 use cursive::{
     direction::Direction,
@@ -6,48 +5,114 @@ use cursive::{
     view::CannotFocus,
     Printer, Vec2,
 };
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use sudoku_generator::cell::Cell;
+use sudoku_generator::killer::Cage;
+use sudoku_generator::variant::Variant;
 
 const CELL_WIDTH: usize = 7;
 const CELL_HEIGHT: usize = 4;
 const BORDER_WIDTH: usize = 63; // 7 * 9
 const BORDER_HEIGHT: usize = 36; // 4 * 9
 
+// the lines between cells, drawn in this order so the later ones win at the corners
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Line {
+    Thin,
+    // between two killer cages
+    Cage,
+    // between two regions, or along the edges of the board
+    Thick,
+}
+
 pub struct BoardView {
-    cells: Arc<Vec<Rc<RefCell<Cell>>>>,
+    cells: Arc<Vec<Arc<RwLock<Cell>>>>,
 
     focused: Option<usize>,
 
     cellviews: Vec<CellView>,
+
+    // the region of each cell, thick borders are drawn between different regions
+    regions: Vec<u8>,
+
+    // the killer cage of each cell, dotted borders are drawn between different cages
+    cages: Vec<usize>,
 }
 
 impl BoardView {
-    pub fn new(cells: Arc<Vec<Rc<RefCell<Cell>>>>) -> Self {
+    pub fn new(cells: Arc<Vec<Arc<RwLock<Cell>>>>) -> Self {
         let mut cellviews = Vec::new();
-        for i in 0..81 {
-            let cell = &cells[i];
-            let cv = CellView::new(Rc::clone(cell));
+        for cell in cells.iter() {
+            let cv = CellView::new(Arc::clone(cell));
             cellviews.push(cv);
         }
         BoardView {
             cells,
             cellviews,
             focused: None,
+            regions: (0..81).map(|i| (i / 27 * 3 + i % 9 / 3) as u8).collect(),
+            cages: vec![0; 81],
+        }
+    }
+
+    // the blocks of the board, or the regions of a jigsaw
+    pub fn set_regions(&mut self, regions: &[u8]) {
+        self.regions = regions.to_vec();
+    }
+
+    // outline the cages of a killer puzzle and label their sums, no cages for the other games
+    pub fn set_cages(&mut self, cages: &[Cage]) {
+        self.cages = vec![0; 81];
+        for cv in &mut self.cellviews {
+            cv.set_sum(None);
+        }
+        for (id, cage) in cages.iter().enumerate() {
+            for &cell in cage.cells() {
+                self.cages[cell as usize] = id + 1;
+            }
+            self.cellviews[cage.cells()[0] as usize].set_sum(Some(cage.sum()));
+        }
+    }
+
+    /**
+     * The kind of line `line` along cell `cell` of the line: the column of a horizontal line,
+     * the row of a vertical one. Region borders and the edges of the board are thick.
+     */
+    fn line(&self, line: usize, cell: usize, horizontal: bool) -> Line {
+        if line == 0 || line == 9 {
+            return Line::Thick;
+        }
+        let (before, after) = if horizontal {
+            ((line - 1) * 9 + cell, line * 9 + cell)
+        } else {
+            (cell * 9 + line - 1, cell * 9 + line)
+        };
+        if self.regions[before] != self.regions[after] {
+            Line::Thick
+        } else if self.cages[before] != self.cages[after] {
+            Line::Cage
+        } else {
+            Line::Thin
+        }
+    }
+
+    // shade the cells in the extra units of the variants
+    pub fn set_variants(&mut self, variants: &[Variant]) {
+        for (index, cv) in self.cellviews.iter_mut().enumerate() {
+            cv.set_shaded(variants.iter().any(|v| v.contains(index as u8)));
         }
     }
 
     pub fn set_folus_cell(&mut self, index: usize) {
         match self.focused {
             Some(i) => {
-                let cv = &mut self.cellviews[i as usize];
+                let cv = &mut self.cellviews[i];
                 cv.set_active(false);
-                let cv2 = &mut self.cellviews[index as usize];
+                let cv2 = &mut self.cellviews[index];
                 cv2.set_active(true);
             }
             _ => {
-                let cv = &mut self.cellviews[index as usize];
+                let cv = &mut self.cellviews[index];
                 cv.set_active(true);
             }
         }
@@ -60,15 +125,13 @@ impl BoardView {
 
         // set highlight cells that has the same value as current focused one
         let item = &self.cells[index];
-        let cell = item.borrow();
+        let cell = item.read().unwrap();
         let is_fixed = cell.is_fixed();
         let v = cell.get_value();
         if is_fixed {
-            for i in 0..81 {
-                let item = &self.cells[i];
-                let c = item.borrow();
+            for (item, cv) in self.cells.iter().zip(self.cellviews.iter_mut()) {
+                let c = item.read().unwrap();
                 if c.get_value() == v {
-                    let cv = &mut self.cellviews[i as usize];
                     cv.set_highlight(true);
                 }
             }
@@ -85,20 +148,35 @@ impl cursive::view::View for BoardView {
             let cv = &self.cellviews[index];
             cv.draw(&printer.offset(Vec2::new(c, r)));
         }
-        // print h lines
-        for i in 0..10 {
-            if i % 3 == 0 {
-                printer.print_hline((0, i * CELL_HEIGHT), BORDER_WIDTH, "#");
-            } else {
-                printer.print_hline((0, i * CELL_HEIGHT), BORDER_WIDTH, "-");
+        // print the thin lines first, then the cage outlines, so the corners of the later ones win
+        for kind in [Line::Thin, Line::Cage, Line::Thick] {
+            // print h lines
+            for i in 0..10 {
+                for j in 0..9 {
+                    if kind == self.line(i, j, true) {
+                        let line = match kind {
+                            Line::Thin => "-",
+                            Line::Cage => "┄",
+                            Line::Thick => "#",
+                        };
+                        let start = (j * CELL_WIDTH, i * CELL_HEIGHT);
+                        printer.print_hline(start, CELL_WIDTH + 1, line);
+                    }
+                }
             }
-        }
-        // print v lines
-        for i in 0..10 {
-            if i % 3 == 0 {
-                printer.print_vline((i * CELL_WIDTH, 0), BORDER_HEIGHT, "#");
-            } else {
-                printer.print_vline((i * CELL_WIDTH, 0), BORDER_HEIGHT, "⎮");
+            // print v lines
+            for i in 0..10 {
+                for j in 0..9 {
+                    if kind == self.line(i, j, false) {
+                        let line = match kind {
+                            Line::Thin => "⎮",
+                            Line::Cage => "┆",
+                            Line::Thick => "#",
+                        };
+                        let start = (i * CELL_WIDTH, j * CELL_HEIGHT);
+                        printer.print_vline(start, CELL_HEIGHT + 1, line);
+                    }
+                }
             }
         }
     }
@@ -154,8 +232,8 @@ impl cursive::view::View for BoardView {
                     if d > 0 {
                         if let Some(index) = self.focused {
                             let cv = &self.cellviews[index];
-                            let item = Rc::clone(&self.cells[index]);
-                            let mut cell = item.try_borrow_mut().unwrap();
+                            let item = Arc::clone(&self.cells[index]);
+                            let mut cell = item.write().unwrap();
                             if matches!(cv.get_mode(), CellMode::Edit) {
                                 if !cell.is_fixed() {
                                     cell.set_value(d as u8);
@@ -165,7 +243,7 @@ impl cursive::view::View for BoardView {
                                             continue;
                                         }
                                         let item = &self.cells[i];
-                                        let c = item.borrow();
+                                        let c = item.read().unwrap();
                                         if c.get_value() == d as u8 {
                                             let cv = &mut self.cellviews[i];
                                             cv.set_highlight(true);
@@ -182,8 +260,8 @@ impl cursive::view::View for BoardView {
                     if let Some(index) = self.focused {
                         let cv = &mut self.cellviews[index];
                         cv.set_mode(CellMode::Draft);
-                        let item = Rc::clone(&self.cells[index]);
-                        let mut cell = item.try_borrow_mut().unwrap();
+                        let item = Arc::clone(&self.cells[index]);
+                        let mut cell = item.write().unwrap();
                         if cell.num_candidates() == 0 {
                             cell.fill_candidates();
                         }
@@ -197,8 +275,8 @@ impl cursive::view::View for BoardView {
                 }
                 if c == 'c' {
                     if let Some(index) = self.focused {
-                        let item = Rc::clone(&self.cells[index]);
-                        let mut cell = item.try_borrow_mut().unwrap();
+                        let item = Arc::clone(&self.cells[index]);
+                        let mut cell = item.write().unwrap();
                         if !cell.is_readonly() {
                             cell.clear_value();
                             cell.clear_candidates();
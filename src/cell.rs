@@ -6,6 +6,7 @@ use std::fmt;
 pub const SOLVED_VALUE: u16 = 511;
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cell {
     value: u8,
     candidates: u16,
@@ -16,6 +17,12 @@ pub fn one_hot(value: u8) -> u16 {
     1 << (value - 1)
 }
 
+impl Default for Cell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Cell {
     pub fn new() -> Self {
         Cell {
@@ -106,6 +113,9 @@ impl Cell {
 
     // apply candidate to self.value if there is only one candidate
     pub fn apply_candidate(&mut self) -> Option<u8> {
+        if self.is_fixed() {
+            return None;
+        }
         match self.num_candidates() {
             1 => {
                 self.value = (self.candidates.trailing_zeros() as u8) + 1;
@@ -127,8 +137,13 @@ impl Cell {
         result
     }
 
+    // candidates as a bit set, bit (c - 1) is set for candidate c
+    pub fn get_candidates(&self) -> u16 {
+        self.candidates
+    }
+
     pub fn candidates_str(&self) -> String {
-        return format!("{:b}", self.candidates);
+        format!("{:b}", self.candidates)
     }
 
     pub fn get_value(&self) -> u8 {
@@ -177,7 +192,7 @@ impl fmt::Display for Cell {
         } else {
             write!(f, ".")?;
         }
-        return Ok(());
+        Ok(())
     }
 }
 
@@ -190,7 +205,7 @@ mod tests {
     fn is_fixed_works() {
         let c = Cell::new();
         println!("{}", c);
-        assert_eq!(c.is_fixed(), false);
+        assert!(!c.is_fixed());
     }
 
     #[test]
@@ -235,34 +250,34 @@ mod tests {
     fn is_remove_candidate_works() {
         let mut c = Cell::new();
         for i in 1..10 {
-            assert_eq!(c.has_candidate(i).0, false);
+            assert!(!c.has_candidate(i).0);
             c.add_candidate(i);
-            assert_eq!(c.has_candidate(i).0, true);
+            assert!(c.has_candidate(i).0);
             c.remove_candidate(i);
-            assert_eq!(c.has_candidate(i).0, false);
+            assert!(!c.has_candidate(i).0);
         }
     }
 
     #[test]
     fn is_has_candidate_works() {
         let mut c = Cell::new();
-        assert_eq!(c.has_candidate(1u8).0, false);
+        assert!(!c.has_candidate(1u8).0);
         c.add_candidate(1u8);
-        assert_eq!(c.has_candidate(1u8).0, true);
+        assert!(c.has_candidate(1u8).0);
 
-        assert_eq!(c.has_candidate(5u8).0, false);
+        assert!(!c.has_candidate(5u8).0);
         c.add_candidate(5u8);
-        assert_eq!(c.has_candidate(5u8).0, true);
+        assert!(c.has_candidate(5u8).0);
 
-        assert_eq!(c.has_candidate(4u8).0, false);
+        assert!(!c.has_candidate(4u8).0);
         c.add_candidate(4u8);
-        assert_eq!(c.has_candidate(4u8).0, true);
+        assert!(c.has_candidate(4u8).0);
 
-        assert_eq!(c.has_candidate(2u8).0, false);
-        assert_eq!(c.has_candidate(3u8).0, false);
-        assert_eq!(c.has_candidate(6u8).0, false);
-        assert_eq!(c.has_candidate(7u8).0, false);
-        assert_eq!(c.has_candidate(9u8).0, false);
+        assert!(!c.has_candidate(2u8).0);
+        assert!(!c.has_candidate(3u8).0);
+        assert!(!c.has_candidate(6u8).0);
+        assert!(!c.has_candidate(7u8).0);
+        assert!(!c.has_candidate(9u8).0);
     }
 
     #[test]
@@ -1,13 +1,15 @@
-use crate::cell::Cell;
 use cursive::{
     direction::Direction,
     event::EventResult,
-    theme::{BaseColor, Color, ColorStyle},
+    theme::{BaseColor, Color, ColorStyle, PaletteColor},
     view::CannotFocus,
     Printer, Rect, Vec2,
 };
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::{Arc, RwLock};
+use sudoku_generator::cell::Cell;
+
+// background of the cells in the extra units of a variant
+const SHADE: Color = Color::Light(BaseColor::Black);
 
 #[derive(Debug, Copy, Clone)]
 pub enum CellMode {
@@ -16,19 +18,25 @@ pub enum CellMode {
 }
 
 pub struct CellView {
-    cell: Rc<RefCell<Cell>>,
+    cell: Arc<RwLock<Cell>>,
     mode: CellMode,
     active: bool,
     highlight: bool,
+    // in an extra unit of the variant being played
+    shaded: bool,
+    // the sum of the killer cage whose top left cell this is
+    sum: Option<u8>,
 }
 
 impl CellView {
-    pub fn new(cell: Rc<RefCell<Cell>>) -> Self {
+    pub fn new(cell: Arc<RwLock<Cell>>) -> Self {
         CellView {
             cell,
             mode: CellMode::Edit,
             active: false,
             highlight: false,
+            shaded: false,
+            sum: None,
         }
     }
 
@@ -40,6 +48,14 @@ impl CellView {
         self.highlight = highlight;
     }
 
+    pub fn set_shaded(&mut self, shaded: bool) {
+        self.shaded = shaded;
+    }
+
+    pub fn set_sum(&mut self, sum: Option<u8>) {
+        self.sum = sum;
+    }
+
     pub fn set_mode(&mut self, mode: CellMode) {
         self.mode = mode;
     }
@@ -51,7 +67,7 @@ impl CellView {
 
 impl cursive::view::View for CellView {
     fn draw(&self, printer: &Printer) {
-        let cellref = self.cell.borrow();
+        let cellref = self.cell.read().unwrap();
 
         // three status: normal, active (currently selected cell), highlight (has the same value as the selected cell)
         // two types of cell: readonly, editable
@@ -60,6 +76,8 @@ impl cursive::view::View for CellView {
                 ColorStyle::title_primary()
             } else if self.highlight {
                 ColorStyle::highlight_inactive()
+            } else if self.shaded {
+                ColorStyle::new(PaletteColor::Secondary, SHADE)
             } else {
                 ColorStyle::secondary()
             }
@@ -68,11 +86,22 @@ impl cursive::view::View for CellView {
                 ColorStyle::highlight()
             } else if self.highlight {
                 ColorStyle::highlight_inactive()
+            } else if self.shaded {
+                ColorStyle::new(PaletteColor::Primary, SHADE)
             } else {
                 ColorStyle::primary()
             }
         };
 
+        if self.shaded {
+            // the background of the whole cell, not only behind the digits
+            printer.with_color(style, |printer| {
+                for r in 0..3 {
+                    printer.print_hline((0, r), 6, " ");
+                }
+            });
+        }
+
         if cellref.is_fixed() {
             printer.with_color(style, |printer| {
                 printer.print((2, 1), format!("{}", cellref.get_value()).as_str())
@@ -80,9 +109,13 @@ impl cursive::view::View for CellView {
         } else {
             for v in 0..9 {
                 let r = v / 3;
-                let c = (v % 3) * 2;
+                // beside the sum of a cage, the candidates close up to the right of the cell
+                let c = match self.sum {
+                    Some(_) => 3 + v % 3,
+                    None => (v % 3) * 2,
+                };
 
-                if cellref.has_candidate(v + 1 as u8).0 && matches!(self.mode, CellMode::Draft) {
+                if cellref.has_candidate(v + 1_u8).0 && matches!(self.mode, CellMode::Draft) {
                     printer.with_color(style, |printer| {
                         printer.print((c as usize, r as usize), format!("{}", v + 1).as_str())
                     });
@@ -93,6 +126,13 @@ impl cursive::view::View for CellView {
                 }
             }
         }
+
+        // the cage sum in the top left corner, left of the candidates
+        if let Some(sum) = self.sum {
+            printer.with_color(ColorStyle::title_secondary(), |printer| {
+                printer.print((0, 0), &sum.to_string())
+            });
+        }
     }
 
     fn take_focus(&mut self, _: Direction) -> Result<EventResult, CannotFocus> {
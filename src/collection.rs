@@ -0,0 +1,284 @@
+//! Line oriented puzzle collections, one puzzle per line as 81 digits and blanks. Empty lines
+//! and lines starting with `#` are skipped, and whatever follows the 81 cells after a separator
+//! is metadata, as in `4.....8.5...  # rated 2.6` or `4.....8.5...,hard`.
+
+use crate::board::{check_game, Board};
+use crate::error::SudokuError;
+use crate::parser::{parse_game, ParseMode};
+use crate::rating::rate;
+use crate::solver::{Dlx, Solver};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/**
+ * The game of a line, None for the lines without a puzzle. The cells may be followed by
+ * metadata, which must not start with a digit or a blank. Positions are counted from the start
+ * of the line.
+ */
+pub fn parse_line(line: &str) -> Option<Result<Vec<u8>, SudokuError>> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return None;
+    }
+    let cells = trimmed
+        .char_indices()
+        .find(|(_, c)| !(c.is_ascii_digit() || matches!(c, '.' | '_' | '*' | '-')))
+        .map_or(trimmed.len(), |(end, _)| end);
+    let (game, metadata) = trimmed.split_at(cells);
+    Some(match metadata.chars().next() {
+        Some(c) if cells < 81 => Err(SudokuError::InvalidCharacter {
+            position: line.len() - line.trim_start().len() + cells,
+            char: c,
+        }),
+        _ => parse_game(game, ParseMode::Strict),
+    })
+}
+
+// the puzzles of a collection with the number of the line each is on, counted from 1
+pub fn read_collection(input: &str) -> Vec<(usize, Result<Vec<u8>, SudokuError>)> {
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(number, line)| parse_line(line).map(|game| (number + 1, game)))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct CollectionOptions {
+    // one thread per cpu by default
+    pub threads: usize,
+    // rating a puzzle takes much longer than solving it
+    pub rate: bool,
+}
+
+impl Default for CollectionOptions {
+    fn default() -> Self {
+        CollectionOptions {
+            threads: num_cpus::get(),
+            rate: true,
+        }
+    }
+}
+
+/**
+ * What was found about the puzzle on a line. A puzzle with several solutions counts 2, and its
+ * solution is one of them.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineResult {
+    pub line: usize,
+    pub givens: String,
+    pub solution: Option<String>,
+    pub count: usize,
+    // the Sudoku Explainer rating of a puzzle with a unique solution
+    pub rating: Option<f32>,
+    pub time: Duration,
+    pub error: Option<SudokuError>,
+}
+
+fn process_game(line: usize, game: Result<Vec<u8>, SudokuError>, rating: bool) -> LineResult {
+    let start = Instant::now();
+    let mut result = LineResult {
+        line,
+        givens: String::new(),
+        solution: None,
+        count: 0,
+        rating: None,
+        time: Duration::ZERO,
+        error: None,
+    };
+    let outcome = game.and_then(|game| {
+        result.givens = game.iter().map(|v| v.to_string()).collect();
+        check_game(&game)?;
+        let mut board = Board::new();
+        board.load_game(&game)?;
+        let solutions = Dlx.enumerate_solutions(&board, 2)?;
+        result.count = solutions.len();
+        let solution = solutions.first().ok_or(SudokuError::NoSolution)?;
+        result.solution = Some(solution.iter().map(|v| v.to_string()).collect());
+        if result.count > 1 {
+            return Err(SudokuError::MultipleSolutions);
+        }
+        if rating {
+            result.rating = Some(rate(&board)?.se);
+        }
+        Ok(())
+    });
+    result.error = outcome.err();
+    result.time = start.elapsed();
+    result
+}
+
+/**
+ * Solve, count and rate every puzzle of a collection on `options.threads` threads. Results are
+ * in the order of the lines, a line that is not a proper puzzle gets its error.
+ */
+pub fn process_collection(input: &str, options: &CollectionOptions) -> Vec<LineResult> {
+    let games = read_collection(input);
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<LineResult> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..options.threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let k = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((line, game)) = games.get(k) else {
+                            break;
+                        };
+                        results.push(process_game(*line, game.clone(), options.rate));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    results.sort_by_key(|result| result.line);
+    results
+}
+
+/**
+ * The results as a table with a header row, its columns separated by `separator`, a tab or a
+ * comma. Fields holding the separator or a quote are quoted.
+ */
+pub fn write_results(results: &[LineResult], separator: char) -> String {
+    let field = |text: &str| -> String {
+        if text.contains([separator, '"', '\n']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    };
+    let mut table = [
+        "line", "puzzle", "solution", "count", "rating", "time_ms", "error",
+    ]
+    .join(&separator.to_string());
+    table.push('\n');
+    for result in results {
+        let row = [
+            result.line.to_string(),
+            result.givens.clone(),
+            result.solution.clone().unwrap_or_default(),
+            result.count.to_string(),
+            result.rating.map_or(String::new(), |se| format!("{se:.1}")),
+            format!("{:.3}", result.time.as_secs_f64() * 1000.0),
+            result
+                .error
+                .as_ref()
+                .map_or(String::new(), |e| e.to_string()),
+        ];
+        let row: Vec<String> = row.iter().map(|text| field(text)).collect();
+        writeln!(table, "{}", row.join(&separator.to_string())).unwrap();
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_line, process_collection, read_collection, write_results, CollectionOptions,
+    };
+    use crate::error::SudokuError;
+
+    const GAME: &str =
+        "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line("  "), None);
+        assert_eq!(parse_line("# a comment"), None);
+        let game = parse_line(GAME).unwrap().unwrap();
+        assert_eq!(game[0], 4);
+        assert_eq!(
+            parse_line(&format!("{GAME}  # rated 2.6")).unwrap(),
+            Ok(game.clone())
+        );
+        assert_eq!(
+            parse_line(&format!("{GAME},hard")).unwrap(),
+            Ok(game.clone())
+        );
+        assert_eq!(parse_line(&format!("{GAME}\t2.6")).unwrap(), Ok(game));
+
+        assert_eq!(
+            parse_line(&GAME[..80]).unwrap(),
+            Err(SudokuError::InvalidLength {
+                expected: 81,
+                found: 80
+            })
+        );
+        assert_eq!(
+            parse_line(&format!(" {}x{}", &GAME[..10], &GAME[11..])).unwrap(),
+            Err(SudokuError::InvalidCharacter {
+                position: 11,
+                char: 'x'
+            })
+        );
+        assert_eq!(
+            parse_line(&format!("{GAME}. # metadata")).unwrap(),
+            Err(SudokuError::InvalidLength {
+                expected: 81,
+                found: 82
+            })
+        );
+        assert_eq!(
+            parse_line("hello").unwrap(),
+            Err(SudokuError::InvalidCharacter {
+                position: 0,
+                char: 'h'
+            })
+        );
+    }
+
+    #[test]
+    fn test_process_collection() {
+        let input = format!(
+            "# collection\n{GAME}\n\n{}\n11{}\n{}\n",
+            &GAME[..50],
+            &GAME[2..],
+            "6285194375794236184137865298.793214.3.214789.194865273781354962235691784946278351",
+        );
+        assert_eq!(read_collection(&input).len(), 4);
+
+        let options = CollectionOptions {
+            threads: 3,
+            rate: true,
+        };
+        let results = process_collection(&input, &options);
+        let lines: Vec<usize> = results.iter().map(|r| r.line).collect();
+        assert_eq!(lines, [2, 4, 5, 6]);
+
+        assert_eq!(results[0].count, 1);
+        assert_eq!(
+            results[0].solution.as_deref(),
+            Some(
+                "417369825632158947958724316825437169791586432346912758289643571573291684164875293"
+            )
+        );
+        assert!(results[0].rating.is_some());
+        assert_eq!(results[0].error, None);
+        assert!(matches!(
+            results[1].error,
+            Some(SudokuError::InvalidLength { .. })
+        ));
+        assert!(matches!(
+            results[2].error,
+            Some(SudokuError::Conflict { .. })
+        ));
+        assert_eq!(results[3].count, 2);
+        assert_eq!(results[3].error, Some(SudokuError::MultipleSolutions));
+
+        let tsv = write_results(&results, '\t');
+        let rows: Vec<&str> = tsv.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert!(rows[0].starts_with("line\tpuzzle\tsolution\tcount"));
+        assert!(rows[1].starts_with("2\t400000805"));
+        let csv = write_results(&results, ',');
+        assert!(csv.contains(",\"expected 81 cells, found 50\""));
+    }
+}
@@ -0,0 +1,319 @@
+//! Extra rules a board can carry on top of its rows, columns and blocks, the foundation of the
+//! variant puzzles: extra all-different regions, sum cages, inequalities, cells that cannot see
+//! each other and so on. A `Constraint` is attached with `Board::add_constraint` or
+//! `Generator::with_constraints`, and every search of the board then respects it: `Board::solve`
+//! and `count_solutions`, the `Solver`s, and the generation and digging of `Generator`.
+//!
+//! The logic solver, the hints and the rating only know the classic rules.
+
+use crate::bitboard::BitBoard;
+use crate::board::{cell_name, UnitId};
+use crate::error::SudokuError;
+use std::fmt;
+use std::sync::Arc;
+
+/**
+ * A rule on the values of a board. `check` is all a rule needs to be respected, since a search
+ * only accepts the full grids that pass it. `propagate` lets a rule prune the search earlier by
+ * removing the candidates it rules out.
+ */
+pub trait Constraint: fmt::Debug + Send + Sync {
+    // a short description of the rule for messages, such as "all different r1c1 r2c2 r3c3"
+    fn describe(&self) -> String;
+
+    // false if the values of a game, 0 for the empty cells, already break the rule
+    fn check(&self, game: &[u8]) -> bool;
+
+    /**
+     * Remove the candidates the rule rules out from a board being searched, with
+     * `BitBoard::eliminate` or `BitBoard::assign`. Returns false on a contradiction. By default
+     * only the placed values are checked.
+     */
+    fn propagate(&self, board: &mut BitBoard) -> bool {
+        self.check(&board.to_game())
+    }
+}
+
+/**
+ * A region whose cells must all hold different digits, like a unit. A region of 9 cells must
+ * hold every digit once.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllDifferent {
+    cells: Vec<u8>,
+}
+
+impl AllDifferent {
+    pub fn new(cells: Vec<u8>) -> Result<Self, SudokuError> {
+        check_region(&cells)?;
+        Ok(AllDifferent { cells })
+    }
+
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+}
+
+impl Constraint for AllDifferent {
+    fn describe(&self) -> String {
+        let cells: Vec<String> = self.cells.iter().map(|&cell| cell_name(cell)).collect();
+        format!("all different {}", cells.join(" "))
+    }
+
+    fn check(&self, game: &[u8]) -> bool {
+        check_all_different(&self.cells, game)
+    }
+
+    fn propagate(&self, board: &mut BitBoard) -> bool {
+        propagate_all_different(&self.cells, board)
+    }
+}
+
+// a unit is a region of 9 cells, which is how the extra units of the variants are checked
+impl Constraint for UnitId {
+    fn describe(&self) -> String {
+        self.to_string()
+    }
+
+    fn check(&self, game: &[u8]) -> bool {
+        check_all_different(self.cells(), game)
+    }
+
+    fn propagate(&self, board: &mut BitBoard) -> bool {
+        propagate_all_different(self.cells(), board)
+    }
+}
+
+// the cells of a region must be on the board, each once, and hold 1 to 9 different digits
+pub(crate) fn check_region(cells: &[u8]) -> Result<(), SudokuError> {
+    if cells.is_empty() || cells.len() > 9 {
+        return Err(SudokuError::InvalidSize { size: cells.len() });
+    }
+    let mut seen = 0u128;
+    for &cell in cells {
+        if cell >= 81 {
+            return Err(SudokuError::InvalidCell { cell });
+        }
+        if seen & (1 << cell) != 0 {
+            return Err(SudokuError::RepeatedCell { cell });
+        }
+        seen |= 1 << cell;
+    }
+    Ok(())
+}
+
+pub(crate) fn check_all_different(cells: &[u8], game: &[u8]) -> bool {
+    let mut seen = 0u16;
+    cells.iter().all(|&cell| match game[cell as usize] {
+        0 => true,
+        value => {
+            let fresh = seen & (1 << (value - 1)) == 0;
+            seen |= 1 << (value - 1);
+            fresh
+        }
+    })
+}
+
+pub(crate) fn propagate_all_different(cells: &[u8], board: &mut BitBoard) -> bool {
+    // placed digits leave the other cells of the region
+    for &cell in cells {
+        let value = board.value(cell);
+        if value == 0 {
+            continue;
+        }
+        for &other in cells {
+            if other != cell && !board.eliminate(other, value) {
+                return false;
+            }
+        }
+    }
+    if cells.len() < 9 {
+        return true;
+    }
+    // a full region has a place for every digit, a digit with a single one goes there
+    for value in 1..=9 {
+        let mut places = cells.iter().filter(|&&cell| {
+            board.value(cell) == value
+                || (board.value(cell) == 0 && board.cell_candidates(cell) & (1 << (value - 1)) != 0)
+        });
+        match (places.next(), places.next()) {
+            (None, _) => return false,
+            (Some(&cell), None) if !board.assign(cell, value) => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+// the first rule a game breaks, as `SudokuError::Unsatisfied`
+pub fn check(game: &[u8], constraints: &[Arc<dyn Constraint>]) -> Result<(), SudokuError> {
+    match constraints
+        .iter()
+        .find(|constraint| !constraint.check(game))
+    {
+        Some(constraint) => Err(SudokuError::Unsatisfied {
+            constraint: constraint.describe(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/**
+ * Let every rule prune the board until none of them removes anything more. Returns false on a
+ * contradiction.
+ */
+pub fn propagate(board: &mut BitBoard, constraints: &[Arc<dyn Constraint>]) -> bool {
+    loop {
+        let before = *board;
+        for constraint in constraints {
+            if !constraint.propagate(board) {
+                return false;
+            }
+        }
+        if *board == before {
+            return true;
+        }
+    }
+}
+
+/**
+ * Like `BitBoard::search`, with the rules propagated after every placement and checked on every
+ * solution. Without any rule this is `BitBoard::search` itself.
+ */
+pub fn search(
+    board: &BitBoard,
+    constraints: &[Arc<dyn Constraint>],
+    limit: usize,
+    found: &mut dyn FnMut(&BitBoard),
+) -> usize {
+    if constraints.is_empty() {
+        return board.search(limit, found);
+    }
+    let mut board = *board;
+    if limit == 0 || !propagate(&mut board, constraints) {
+        return 0;
+    }
+    let (index, candidates) = match board.next_cell() {
+        Some(cell) => cell,
+        None => {
+            if check(&board.to_game(), constraints).is_err() {
+                return 0;
+            }
+            found(&board);
+            return 1;
+        }
+    };
+
+    let mut count = 0;
+    for value in 1..=9 {
+        if candidates & (1 << (value - 1)) == 0 {
+            continue;
+        }
+        let mut next = board;
+        if next.assign(index, value) {
+            count += search(&next, constraints, limit - count, found);
+            if count >= limit {
+                break;
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, propagate, search, AllDifferent, Constraint};
+    use crate::bitboard::BitBoard;
+    use crate::error::SudokuError;
+    use std::sync::Arc;
+
+    // two cells that must not hold consecutive digits, a rule that only checks
+    #[derive(Debug)]
+    struct NotConsecutive(u8, u8);
+
+    impl Constraint for NotConsecutive {
+        fn describe(&self) -> String {
+            format!("not consecutive {} {}", self.0, self.1)
+        }
+
+        fn check(&self, game: &[u8]) -> bool {
+            let (a, b) = (game[self.0 as usize], game[self.1 as usize]);
+            a == 0 || b == 0 || a.abs_diff(b) != 1
+        }
+    }
+
+    fn diagonal() -> AllDifferent {
+        AllDifferent::new((0..9).map(|i| i * 10).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_all_different() {
+        let diagonal = diagonal();
+        assert_eq!(
+            diagonal.describe(),
+            "all different r1c1 r2c2 r3c3 r4c4 r5c5 r6c6 r7c7 r8c8 r9c9"
+        );
+        let mut game = vec![0; 81];
+        game[0] = 5;
+        assert!(diagonal.check(&game));
+        game[80] = 5;
+        assert!(!diagonal.check(&game));
+        let constraints: Vec<Arc<dyn Constraint>> = vec![Arc::new(diagonal.clone())];
+        assert_eq!(
+            check(&game, &constraints),
+            Err(SudokuError::Unsatisfied {
+                constraint: diagonal.describe()
+            })
+        );
+
+        // 1 to 8 on the diagonal leave 9 for the last cell
+        let mut board = BitBoard::new();
+        for i in 0..8 {
+            assert!(board.assign(i * 10, i + 1));
+        }
+        assert_eq!(board.value(80), 0);
+        assert!(propagate(&mut board, &constraints));
+        assert_eq!(board.value(80), 9);
+
+        assert_eq!(
+            AllDifferent::new((0..10).collect()),
+            Err(SudokuError::InvalidSize { size: 10 })
+        );
+        assert_eq!(
+            AllDifferent::new(vec![]),
+            Err(SudokuError::InvalidSize { size: 0 })
+        );
+        assert_eq!(
+            AllDifferent::new(vec![0, 81]),
+            Err(SudokuError::InvalidCell { cell: 81 })
+        );
+        assert_eq!(
+            AllDifferent::new(vec![0, 0]),
+            Err(SudokuError::RepeatedCell { cell: 0 })
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let board = BitBoard::new();
+        let constraints: Vec<Arc<dyn Constraint>> =
+            vec![Arc::new(diagonal()), Arc::new(NotConsecutive(0, 1))];
+        let mut solutions = Vec::new();
+        let count = search(&board, &constraints, 5, &mut |solution| {
+            solutions.push(solution.to_game())
+        });
+        assert_eq!(count, 5);
+        for solution in solutions {
+            assert!(check(&solution, &constraints).is_ok());
+            assert!(!solution.contains(&0));
+        }
+
+        // r1c1 and r1c2 can only be 1 and 2 here
+        let mut board = BitBoard::new();
+        for (i, value) in (2..9).zip(3..=9) {
+            assert!(board.assign(i, value));
+        }
+        assert_eq!(search(&board, &constraints, 1, &mut |_| {}), 0);
+        assert!(search(&board, &[], 1, &mut |_| {}) > 0);
+    }
+}
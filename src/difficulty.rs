@@ -0,0 +1,58 @@
+use crate::board::Board;
+use crate::error::SudokuError;
+use crate::rating::rate;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Extreme,
+}
+
+/**
+ * Grade a puzzle from its Sudoku Explainer rating, see `rating::rate`:
+ * Easy needs hidden singles only, Medium naked singles and intersections, Hard subsets up to
+ * triples and basic fish, Expert wings, quads, uniqueness and colouring, and Extreme guesses.
+ * The puzzle must have a unique solution.
+ */
+pub fn grade(game: &[u8]) -> Result<Difficulty, SudokuError> {
+    let mut board = Board::new();
+    board.load_game(game)?;
+    Ok(rate(&board)?.difficulty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{grade, Difficulty};
+    use crate::board::game_str_to_vec;
+
+    #[test]
+    fn test_grade() {
+        // a solution with a single hole
+        let game =
+            "62851943757942361841378652985793214636214789519486527378135496223569178494627835.";
+        let v = game_str_to_vec(game).unwrap();
+        assert_eq!(grade(&v), Ok(Difficulty::Easy));
+
+        // needs pointing pairs
+        let game =
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let v = game_str_to_vec(game).unwrap();
+        assert_eq!(grade(&v), Ok(Difficulty::Medium));
+
+        // needs an XY-Wing
+        let game =
+            ".1.7....8...6.3.7.......5....1974..2.........6..8.27.98...3.42...2.9...734.....9.";
+        let v = game_str_to_vec(game).unwrap();
+        assert_eq!(grade(&v), Ok(Difficulty::Expert));
+    }
+
+    #[test]
+    fn test_difficulty_order() {
+        assert!(Difficulty::Easy < Difficulty::Medium);
+        assert!(Difficulty::Expert < Difficulty::Extreme);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::board::{cell_name, UnitId};
+use std::error::Error;
+use std::fmt;
+
+/**
+ * Everything that can be wrong with a game given to the library, or with a search on it.
+ * Positions and cells are numbered from 0, the messages name cells like r4c8.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SudokuError {
+    // a game must have one value per cell
+    InvalidLength { expected: usize, found: usize },
+    // a character that is neither a digit nor a blank, at its position in the input
+    InvalidCharacter { position: usize, char: char },
+    // a value above 9 in a cell, or 0 where a digit is placed
+    InvalidDigit { cell: u8, digit: u8 },
+    // a cell index past the 81 cells of the board
+    InvalidCell { cell: u8 },
+    // a cell listed twice in the region of a rule
+    RepeatedCell { cell: u8 },
+    // a region of a rule that does not hold 1 to 9 cells
+    InvalidSize { size: usize },
+    // no `size` different digits add up to the sum of a cage
+    InvalidSum { sum: u8, size: usize },
+    // `digit` is already placed in `unit`, elsewhere than in `cell`
+    Conflict { cell: u8, digit: u8, unit: UnitId },
+    // the values break an extra rule of the board, named by `Constraint::describe`
+    Unsatisfied { constraint: String },
+    NoSolution,
+    MultipleSolutions,
+    // the time budget ran out before the search succeeded
+    Timeout,
+    // the caller stopped the search, see `generator::Progress`
+    Cancelled,
+}
+
+impl fmt::Display for SudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SudokuError::InvalidLength { expected, found } => {
+                write!(f, "expected {expected} cells, found {found}")
+            }
+            SudokuError::InvalidCharacter { position, char } => {
+                write!(f, "invalid character {char:?} at position {position}")
+            }
+            SudokuError::InvalidDigit { cell, digit } => {
+                write!(
+                    f,
+                    "{digit} in {} is not a digit from 1 to 9",
+                    cell_name(*cell)
+                )
+            }
+            SudokuError::InvalidCell { cell } => {
+                write!(f, "cell {cell} is not on the board, the cells are 0 to 80")
+            }
+            SudokuError::RepeatedCell { cell } => {
+                write!(f, "{} is listed twice in a region", cell_name(*cell))
+            }
+            SudokuError::InvalidSize { size } => {
+                write!(f, "a region of {size} cells, a region holds 1 to 9 cells")
+            }
+            SudokuError::InvalidSum { sum, size } => {
+                write!(f, "no {size} different digits add up to {sum}")
+            }
+            SudokuError::Conflict { cell, digit, unit } => {
+                write!(f, "{digit} in {} is already in {unit}", cell_name(*cell))
+            }
+            SudokuError::Unsatisfied { constraint } => {
+                write!(f, "the rule {constraint} is broken")
+            }
+            SudokuError::NoSolution => write!(f, "the puzzle has no solution"),
+            SudokuError::MultipleSolutions => write!(f, "the puzzle has more than one solution"),
+            SudokuError::Timeout => write!(f, "the time budget ran out"),
+            SudokuError::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl Error for SudokuError {}
+
+#[cfg(test)]
+mod tests {
+    use super::SudokuError;
+    use crate::board::UnitId;
+
+    #[test]
+    fn test_display() {
+        let error = SudokuError::Conflict {
+            cell: 35,
+            digit: 7,
+            unit: UnitId::Block(5),
+        };
+        assert_eq!(error.to_string(), "7 in r4c9 is already in block 6");
+        let error = SudokuError::InvalidCharacter {
+            position: 3,
+            char: 'x',
+        };
+        assert_eq!(error.to_string(), "invalid character 'x' at position 3");
+        let error = SudokuError::InvalidDigit { cell: 0, digit: 12 };
+        assert_eq!(error.to_string(), "12 in r1c1 is not a digit from 1 to 9");
+        let error = SudokuError::InvalidSum { sum: 30, size: 3 };
+        assert_eq!(error.to_string(), "no 3 different digits add up to 30");
+    }
+}
@@ -0,0 +1,111 @@
+//! Readers and writers for the files of other sudoku programs. Every reader marks the givens
+//! readonly, values entered by the player stay editable, and the writers tell them apart the
+//! same way, see `Board::givens`.
+
+use crate::board::Board;
+use crate::error::SudokuError;
+use crate::parser::{parse_game, ParseMode};
+use std::slice;
+
+pub mod opensudoku;
+pub mod sdk;
+pub mod sdx;
+pub mod ss;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    // SadMan Sudoku
+    Sdk,
+    // SudoCue, with candidates and the player's values
+    Sdx,
+    // Simple Sudoku
+    Ss,
+    // an OpenSudoku collection
+    OpenSudoku,
+}
+
+impl Format {
+    pub fn from_extension(extension: &str) -> Option<Format> {
+        match extension.to_ascii_lowercase().as_str() {
+            "sdk" => Some(Format::Sdk),
+            "sdx" => Some(Format::Sdx),
+            "ss" => Some(Format::Ss),
+            "opensudoku" | "xml" => Some(Format::OpenSudoku),
+            _ => None,
+        }
+    }
+
+    // the games of a file, a single one except for collections
+    pub fn read(&self, input: &str) -> Result<Vec<Board>, SudokuError> {
+        match self {
+            Format::Sdk => sdk::read(input).map(|board| vec![board]),
+            Format::Sdx => sdx::read(input).map(|board| vec![board]),
+            Format::Ss => ss::read(input).map(|board| vec![board]),
+            Format::OpenSudoku => opensudoku::read(input),
+        }
+    }
+
+    pub fn write(&self, board: &Board) -> String {
+        match self {
+            Format::Sdk => sdk::write(board),
+            Format::Sdx => sdx::write(board),
+            Format::Ss => ss::write(board),
+            Format::OpenSudoku => opensudoku::write_collection("", slice::from_ref(board)),
+        }
+    }
+}
+
+// a board whose fixed cells are all givens
+fn puzzle_board(game: &[u8]) -> Result<Board, SudokuError> {
+    let mut board = Board::new();
+    board.load_game(game)?;
+    for cell in &mut board.cells {
+        if cell.is_fixed() {
+            cell.set_readonly(true);
+        }
+    }
+    Ok(board)
+}
+
+// the 81 cells of text laid out in rows, ignoring the drawing of the grid
+fn parse_rows(rows: &str) -> Result<Vec<u8>, SudokuError> {
+    parse_game(rows, ParseMode::Lenient)
+}
+
+// a game as 9 rows of 9 characters
+fn write_rows(game: &[u8], blank: char) -> String {
+    let mut result = String::with_capacity(90);
+    for row in game.chunks(9) {
+        for value in row {
+            result.push(match value {
+                0 => blank,
+                v => char::from(b'0' + v),
+            });
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Format;
+    use crate::board::{game_str_to_vec, Board};
+
+    #[test]
+    fn test_formats() {
+        let game =
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let mut board = Board::new();
+        board.load_game(&game_str_to_vec(game).unwrap()).unwrap();
+        for format in [Format::Sdk, Format::Sdx, Format::Ss, Format::OpenSudoku] {
+            let boards = format.read(&format.write(&board)).unwrap();
+            assert_eq!(boards.len(), 1);
+            assert_eq!(boards[0].serialize(), game);
+            assert!(boards[0].cells[0].is_readonly());
+            assert!(!boards[0].cells[1].is_readonly());
+        }
+        assert_eq!(Format::from_extension("SDK"), Some(Format::Sdk));
+        assert_eq!(Format::from_extension("txt"), None);
+    }
+}
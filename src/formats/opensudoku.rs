@@ -0,0 +1,219 @@
+//! OpenSudoku collections, an XML file with the givens of each game in the `data` attribute of
+//! a `game` element, `0` for the empty cells:
+//!
+//! ```text
+//! <?xml version="1.0" encoding="UTF-8"?>
+//! <opensudoku>
+//!   <name>Collection</name>
+//!   <game data="400000805030000000..." />
+//! </opensudoku>
+//! ```
+//!
+//! Only the givens are read and written.
+
+use super::puzzle_board;
+use crate::board::Board;
+use crate::error::SudokuError;
+use crate::parser::{parse_game, ParseMode};
+
+// every game of a collection, in order
+pub fn read(input: &str) -> Result<Vec<Board>, SudokuError> {
+    let mut boards = Vec::new();
+    let mut rest = input;
+    while let Some(start) = rest.find("<game") {
+        let element = &rest[start..];
+        let end = element.find('>').unwrap_or(element.len());
+        if let Some((offset, data)) = attribute(&element[..end], "data") {
+            let game =
+                parse_game(&unescape(data), ParseMode::Strict).map_err(|error| match error {
+                    SudokuError::InvalidCharacter { position, char } => {
+                        let before = input.len() - rest.len() + start + offset;
+                        SudokuError::InvalidCharacter {
+                            position: input[..before].chars().count()
+                                + raw_position(data, position),
+                            char,
+                        }
+                    }
+                    error => error,
+                })?;
+            boards.push(puzzle_board(&game)?);
+        }
+        rest = &element[end..];
+    }
+    Ok(boards)
+}
+
+// the raw value of an attribute of an element and its byte offset in the element, the
+// attributes are read in turn so that a name inside another name or value does not match
+fn attribute<'a>(element: &'a str, name: &str) -> Option<(usize, &'a str)> {
+    // skip the element name
+    let mut rest = element.trim_start_matches('<');
+    rest = rest.trim_start_matches(|c: char| !c.is_whitespace() && c != '/' && c != '>');
+    loop {
+        rest = rest.trim_start();
+        let length = rest.find(|c: char| c == '=' || c.is_whitespace() || c == '/' || c == '>')?;
+        let (key, after) = rest.split_at(length);
+        let after = after.trim_start().strip_prefix('=')?.trim_start();
+        let quote = after.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        let value = &after[1..];
+        let end = value.find(quote)?;
+        if key == name {
+            let offset = element.len() - value.len();
+            return Some((offset, &value[..end]));
+        }
+        rest = &value[end + 1..];
+    }
+}
+
+// the text of an attribute value with its entity references replaced
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match rest
+            .find(';')
+            .and_then(|end| Some((entity(&rest[1..end])?, end)))
+        {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+// the character of an entity reference, without its `&` and `;`
+fn entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => {
+            let code = match name.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => name.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+// the position in a raw value of the character at `position` in its unescaped text
+fn raw_position(text: &str, position: usize) -> usize {
+    let mut raw = 0;
+    let mut rest = text;
+    for _ in 0..position {
+        let next = match rest.find(';') {
+            Some(end) if rest.starts_with('&') && entity(&rest[1..end]).is_some() => end + 1,
+            _ => rest.chars().next().map_or(0, char::len_utf8),
+        };
+        raw += rest[..next].chars().count();
+        rest = &rest[next..];
+    }
+    raw
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn write_collection(name: &str, boards: &[Board]) -> String {
+    let mut result = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opensudoku>\n");
+    if !name.is_empty() {
+        result.push_str(&format!("  <name>{}</name>\n", escape(name)));
+    }
+    for board in boards {
+        let data: String = board.givens().iter().map(|v| v.to_string()).collect();
+        result.push_str(&format!("  <game data=\"{data}\" />\n"));
+    }
+    result.push_str("</opensudoku>\n");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read, write_collection};
+    use crate::board::{game_str_to_vec, Board};
+    use crate::error::SudokuError;
+
+    const GAMES: [&str; 2] = [
+        "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......",
+        "6....5.......217...58.....3....4..9......7.1..9.582.7.3.7..85...86..........7....",
+    ];
+
+    #[test]
+    fn test_collection() {
+        let boards: Vec<Board> = GAMES
+            .iter()
+            .map(|game| {
+                let mut board = Board::new();
+                board.load_game(&game_str_to_vec(game).unwrap()).unwrap();
+                board
+            })
+            .collect();
+        let file = write_collection("Hard & fun", &boards);
+        assert!(file.contains("<name>Hard &amp; fun</name>"));
+        assert!(file.contains("<game data=\"400000805030"));
+
+        let read_boards = read(&file).unwrap();
+        assert_eq!(read_boards.len(), 2);
+        for (board, game) in read_boards.iter().zip(GAMES) {
+            assert_eq!(board.serialize(), game);
+            assert!(board.cells[0].is_readonly());
+        }
+    }
+
+    #[test]
+    fn test_opensudoku_file() {
+        let file = format!(
+            "<opensudoku>\n<game created='1' data='{}'/><game data=\"{}\" state=\"1\"/>\n</opensudoku>",
+            GAMES[0].replace('.', "0"),
+            GAMES[1].replace('.', "0"),
+        );
+        assert_eq!(read(&file).unwrap().len(), 2);
+        assert!(read("<opensudoku/>").unwrap().is_empty());
+
+        // only the data attribute itself holds the game, with its entities replaced
+        let other = format!(
+            "<game metadata=\"1\" title='data=\"2\"' data=\"&#52;{}\" />",
+            &GAMES[0][1..]
+        );
+        let boards = read(&other).unwrap();
+        assert_eq!(boards.len(), 1);
+        assert_eq!(boards[0].serialize(), GAMES[0]);
+        let wrong = other.replacen("8", "&amp;", 1);
+        assert_eq!(
+            read(&wrong).err(),
+            Some(SudokuError::InvalidCharacter {
+                position: wrong.find("&amp;").unwrap(),
+                char: '&'
+            })
+        );
+
+        let wrong = file.replacen("4", "x", 1);
+        let position = wrong.find('x').unwrap();
+        assert_eq!(
+            read(&wrong).err(),
+            Some(SudokuError::InvalidCharacter {
+                position,
+                char: 'x'
+            })
+        );
+    }
+}
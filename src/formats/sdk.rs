@@ -0,0 +1,125 @@
+//! SadMan Sudoku files. A puzzle is 9 rows of digits and `.`, after optional `#` comment lines.
+//! A saved game puts them in a `[Puzzle]` section and the grid as played in a `[State]`
+//! section:
+//!
+//! ```text
+//! [Puzzle]
+//! 4.....8.5
+//! ...
+//! [State]
+//! 41....8.5
+//! ...
+//! ```
+
+use super::{parse_rows, puzzle_board, write_rows};
+use crate::board::Board;
+use crate::error::SudokuError;
+
+#[derive(PartialEq)]
+enum Section {
+    Puzzle,
+    State,
+    Other,
+}
+
+/**
+ * Read a puzzle, the values of its `[State]` section that are not givens are the player's.
+ * Sections other than `[Puzzle]` and `[State]` are skipped.
+ */
+pub fn read(input: &str) -> Result<Board, SudokuError> {
+    let mut puzzle = String::new();
+    let mut state = String::new();
+    let mut section = Section::Puzzle;
+    for line in input.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            section = match &line[1..line.len() - 1] {
+                "Puzzle" => Section::Puzzle,
+                "State" => Section::State,
+                _ => Section::Other,
+            };
+            continue;
+        }
+        let text = match section {
+            Section::Puzzle => &mut puzzle,
+            Section::State => &mut state,
+            Section::Other => continue,
+        };
+        text.push_str(line);
+        text.push('\n');
+    }
+
+    let mut board = puzzle_board(&parse_rows(&puzzle)?)?;
+    if !state.is_empty() {
+        for (cell, value) in board.cells.iter_mut().zip(parse_rows(&state)?) {
+            if !cell.is_readonly() && value > 0 {
+                cell.set_value(value);
+            }
+        }
+    }
+    Ok(board)
+}
+
+// a puzzle alone is written as is, a game being played in sections
+pub fn write(board: &Board) -> String {
+    let givens = board.givens();
+    let values: Vec<u8> = board.cells.iter().map(|cell| cell.get_value()).collect();
+    if values == givens {
+        return write_rows(&givens, '.');
+    }
+    format!(
+        "[Puzzle]\n{}[State]\n{}",
+        write_rows(&givens, '.'),
+        write_rows(&values, '.')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read, write};
+    use crate::error::SudokuError;
+
+    const PUZZLE: &str = "\
+4.....8.5
+.3.......
+...7.....
+.2.....6.
+....8.4..
+....1....
+...6.3.7.
+5..2.....
+1.4......
+";
+
+    #[test]
+    fn test_puzzle() {
+        let file = format!("#Aauthor\n#Ddescription\n{PUZZLE}");
+        let board = read(&file).unwrap();
+        assert_eq!(board.cells[0].get_value(), 4);
+        assert!(board.cells[0].is_readonly());
+        assert_eq!(write(&board), PUZZLE);
+
+        assert!(matches!(
+            read(&PUZZLE[10..]),
+            Err(SudokuError::InvalidLength { found: 72, .. })
+        ));
+    }
+
+    #[test]
+    fn test_state() {
+        let state = PUZZLE.replacen("4.....", "41.2..", 1);
+        let file = format!("[Puzzle]\n{PUZZLE}[State]\n{state}[Other]\nignored\n");
+        let board = read(&file).unwrap();
+        assert_eq!(board.cells[1].get_value(), 1);
+        assert!(!board.cells[1].is_readonly());
+        assert_eq!(board.cells[3].get_value(), 2);
+        assert_eq!(board.givens()[1], 0);
+
+        let written = write(&board);
+        assert_eq!(written, format!("[Puzzle]\n{PUZZLE}[State]\n{state}"));
+        assert_eq!(read(&written).unwrap().serialize(), board.serialize());
+    }
+}
@@ -0,0 +1,155 @@
+//! SudoCue files, a row of 9 cells per line. A given is its digit, a value placed by the player
+//! is its digit after `u`, and an empty cell lists its candidates, `0` if it has no pencil
+//! marks. A single candidate is written twice, so that it does not read as a given.
+//!
+//! ```text
+//! 4 1679 u2 139 2369 1369 8 1239 5
+//! ...
+//! ```
+
+use crate::board::Board;
+use crate::error::SudokuError;
+use crate::logic::digits;
+
+pub fn read(input: &str) -> Result<Board, SudokuError> {
+    let mut board = Board::new();
+    let mut cells = 0;
+    let mut position = 0;
+    for line in input.split_inclusive('\n') {
+        let mut token = String::new();
+        let mut start = position;
+        for c in line.chars().chain([' ']) {
+            if !c.is_whitespace() {
+                if token.is_empty() {
+                    start = position;
+                }
+                token.push(c);
+            } else if !token.is_empty() {
+                if cells < 81 {
+                    read_cell(&mut board, cells, &token, start)?;
+                }
+                cells += 1;
+                token.clear();
+            }
+            position += 1;
+        }
+        // the space chained above is not part of the input
+        position -= 1;
+    }
+    if cells != 81 {
+        return Err(SudokuError::InvalidLength {
+            expected: 81,
+            found: cells,
+        });
+    }
+    Ok(board)
+}
+
+// read the cell at `index` from its token, found at `position` in the input
+fn read_cell(
+    board: &mut Board,
+    index: usize,
+    token: &str,
+    position: usize,
+) -> Result<(), SudokuError> {
+    let user = token.starts_with('u');
+    let mut mask: u16 = 0;
+    for (k, c) in token.chars().enumerate().skip(user as usize) {
+        match c.to_digit(10) {
+            Some(0) if token.len() == 1 => {}
+            Some(digit @ 1..=9) => mask |= 1 << (digit - 1),
+            _ => {
+                return Err(SudokuError::InvalidCharacter {
+                    position: position + k,
+                    char: c,
+                })
+            }
+        }
+    }
+    let cell = &mut board.cells[index];
+    if user && mask.count_ones() != 1 {
+        return Err(SudokuError::InvalidCharacter {
+            position,
+            char: 'u',
+        });
+    } else if user || (token.len() == 1 && mask != 0) {
+        cell.restore((mask.trailing_zeros() as u8 + 1, 0));
+        cell.set_readonly(!user);
+    } else {
+        cell.restore((0, mask));
+    }
+    Ok(())
+}
+
+pub fn write(board: &Board) -> String {
+    let givens = board.givens();
+    let mut result = String::new();
+    for (index, cell) in board.cells.iter().enumerate() {
+        let token = if givens[index] > 0 {
+            givens[index].to_string()
+        } else if cell.is_fixed() {
+            format!("u{}", cell.get_value())
+        } else if cell.get_candidates() == 0 {
+            "0".to_string()
+        } else if cell.get_candidates().count_ones() == 1 {
+            digits(cell.get_candidates())[0].to_string().repeat(2)
+        } else {
+            digits(cell.get_candidates())
+                .iter()
+                .map(|d| d.to_string())
+                .collect()
+        };
+        result.push_str(&token);
+        result.push(if index % 9 == 8 { '\n' } else { ' ' });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read, write};
+    use crate::board::{game_str_to_vec, Board};
+    use crate::error::SudokuError;
+
+    #[test]
+    fn test_sdx() {
+        let game =
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let mut board = Board::new();
+        board.load_candidates(&game_str_to_vec(game).unwrap());
+        for cell in &mut board.cells {
+            cell.set_readonly(cell.is_fixed());
+        }
+        board.cells[1].restore((1, 0));
+        board.cells[2].restore((0, 0));
+        // a single candidate is not a given
+        board.cells[4].restore((0, 0b100));
+
+        let file = write(&board);
+        assert!(file.starts_with("4 u1 0 139 33 "));
+        assert_eq!(file.lines().count(), 9);
+        let other = read(&file).unwrap();
+        assert_eq!(other.backup(), board.backup());
+        assert_eq!(other.givens(), board.givens());
+
+        assert_eq!(
+            read(&file.replacen("u1", "u12", 1)).err(),
+            Some(SudokuError::InvalidCharacter {
+                position: 2,
+                char: 'u'
+            })
+        );
+        let wrong = file.replacen("139", "1x9", 1);
+        assert_eq!(
+            read(&wrong).err(),
+            Some(SudokuError::InvalidCharacter {
+                position: wrong.find('x').unwrap(),
+                char: 'x'
+            })
+        );
+        assert!(matches!(
+            read("1 2 3"),
+            Err(SudokuError::InvalidLength { found: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,73 @@
+//! Simple Sudoku files: the givens drawn as a grid, with `.` or `X` for the empty cells.
+//!
+//! ```text
+//! 4..|...|8.5
+//! .3.|...|...
+//! ...|7..|...
+//! -----------
+//! ...
+//! ```
+
+use super::{parse_rows, puzzle_board};
+use crate::board::Board;
+use crate::error::SudokuError;
+
+pub fn read(input: &str) -> Result<Board, SudokuError> {
+    let rows = input.replace(['X', 'x'], ".");
+    puzzle_board(&parse_rows(&rows)?)
+}
+
+pub fn write(board: &Board) -> String {
+    let givens = board.givens();
+    let mut result = String::new();
+    for (r, row) in givens.chunks(9).enumerate() {
+        if r == 3 || r == 6 {
+            result.push_str("-----------\n");
+        }
+        for (c, value) in row.iter().enumerate() {
+            if c == 3 || c == 6 {
+                result.push('|');
+            }
+            result.push(match value {
+                0 => '.',
+                v => char::from(b'0' + v),
+            });
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read, write};
+
+    const FILE: &str = "\
+4..|...|8.5
+.3.|...|...
+...|7..|...
+-----------
+.2.|...|.6.
+...|.8.|4..
+...|.1.|...
+-----------
+...|6.3|.7.
+5..|2..|...
+1.4|...|...
+";
+
+    #[test]
+    fn test_ss() {
+        let board = read(FILE).unwrap();
+        assert_eq!(
+            board.serialize(),
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......"
+        );
+        assert!(board.cells[0].is_readonly());
+        assert_eq!(write(&board), FILE);
+        assert_eq!(
+            read(&FILE.replace('.', "X")).unwrap().serialize(),
+            board.serialize()
+        );
+    }
+}
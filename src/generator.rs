@@ -1,201 +1,655 @@
-use crate::{board::game_str_to_vec, board::Board};
-use rand::{self, prelude::SliceRandom, thread_rng};
+use crate::bitboard::BitBoard;
+use crate::board::{check_game, game_str_to_vec};
+use crate::constraint::{self, Constraint};
+use crate::difficulty::{grade, Difficulty};
+use crate::error::SudokuError;
+use crate::grid::search::GridState;
+use crate::grid::{is_connected, neighbours, Geometry, Grid, MAX_SIZE};
+use crate::killer::{self, Cage};
+use crate::logic::digits;
+use crate::solver::unique_solution;
+use crate::variant::Variant;
+use rand::{self, prelude::SliceRandom, thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 const MIN_CELLS: u8 = 17;
 
-pub fn generate_game() -> String {
-    let mut board = Board::new();
+// time `generate_puzzle` may spend looking for a puzzle of the requested difficulty
+const DEFAULT_TIME_BUDGET: Duration = Duration::from_secs(10);
 
-    loop {
-        board.reset();
-        board.fill_candidates();
+/**
+ * Number of candidate attempts a random initial game may take to be solved before
+ * `generate_game` gives up on it and restarts with another one. It only bounds the time
+ * spent on unlucky starting points, every uniqueness check runs without a limit.
+ */
+const RESTART_BUDGET: usize = 1000;
 
-        // set 1-9 randomly to nine of the cells
-        for i in 0..9 {
-            let index = select_non_fixed(&board);
-            board.assign_cell(index, i + 1);
-        }
+// like `RESTART_BUDGET`, for the solution grids of `generate_grid`
+const GRID_RESTART_BUDGET: usize = 10_000;
 
-        try_init_game(&mut board);
-        let (solved, _) = try_solve_game(&mut board); // board.solve_concurrent(); //
+// number of unlucky searches `generate_grid` restarts before it searches without a budget
+const GRID_RESTARTS: usize = 100;
 
-        if !solved {
-            println!("can not solve, try another init");
-        } else {
-            break;
-        }
-    }
+/**
+ * Number of candidate attempts `dig_grid` may spend proving that a cell can be dug out. A cell
+ * whose proof takes longer is kept, so the puzzle stays unique, only with a few more givens.
+ */
+const GRID_DIG_BUDGET: usize = 100;
+
+// cells swapped between regions for a jigsaw layout, per cell of the grid
+const JIGSAW_SWAPS: usize = 4;
+
+// the largest cage `generate_killer` grows, a lone cell left over may still join a full one
+const KILLER_CAGE_SIZE: usize = 5;
+
+/**
+ * What the generator reports while it works, see `Generator::generate_game_with` and
+ * `Generator::dig_holes_with`.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Progress {
+    // the random initial game was dropped for another one, with the number of restarts so far
+    Restart(usize),
+    // a cell was dug out, with the number of cells dug so far
+    Dug(usize),
+}
 
-    board.serialize()
+#[derive(Debug, PartialEq, Eq)]
+enum SolveOutcome {
+    Solved,
+    Unsolvable,
+    OutOfBudget,
 }
 
-pub fn dig_holes(game: &str) -> String {
-    let remain_cells = MIN_CELLS + random_index(6);
-    let mut can_dig_cells = [true; 81];
-    let mut game_vec = game_str_to_vec(game).unwrap();
-    let mut total = 81;
+/**
+ * A generator owns the random number generator used by every random choice, so the same seed
+ * always produces the same solutions and puzzles. Seeded generators use ChaCha8, whose stream is
+ * fixed across rand versions and platforms, so a seed can be shared as a puzzle number.
+ */
+pub struct Generator<R: Rng = ChaCha8Rng> {
+    rng: R,
+    constraints: Vec<Arc<dyn Constraint>>,
+}
 
-    while total > remain_cells {
-        let start = next_diggable_index(&can_dig_cells);
-        if start > 80 {
-            break;
+impl Generator<ChaCha8Rng> {
+    pub fn from_seed(seed: u64) -> Self {
+        Generator::new(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+impl<R: Rng> Generator<R> {
+    pub fn new(rng: R) -> Self {
+        Generator {
+            rng,
+            constraints: Vec::new(),
         }
+    }
 
-        if is_game_has_unique_solution(&mut game_vec, start as usize) {
-            game_vec[start] = 0;
-            total -= 1;
+    /**
+     * Make every solution follow extra rules, and dig puzzles whose solution is unique under
+     * them. The grading of `generate_puzzle` only knows the classic rules, so it keeps the
+     * cells that only the extra rules pin down.
+     */
+    pub fn with_constraints(mut self, constraints: Vec<Arc<dyn Constraint>>) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    // like `with_constraints`, adding the units of the variants to the rules
+    pub fn with_variants(mut self, variants: &[Variant]) -> Self {
+        for variant in variants {
+            self.constraints.extend(variant.constraints());
         }
-        can_dig_cells[start] = false;
+        self
     }
-    game_vec.into_iter().map(|item| item.to_string()).collect()
-}
 
-fn next_diggable_index(diggable_cells: &[bool; 81]) -> usize {
-    let mut candidates = Vec::new();
+    pub fn generate_game(&mut self) -> String {
+        self.generate_game_with(&mut |_| true).unwrap()
+    }
 
-    for index in 0..81 {
-        if diggable_cells[index] {
-            candidates.push(index);
+    /**
+     * Like `generate_game`, reporting every restart to `observer`. Generation stops with
+     * `SudokuError::Cancelled` as soon as the observer returns false.
+     */
+    pub fn generate_game_with(
+        &mut self,
+        observer: &mut dyn FnMut(Progress) -> bool,
+    ) -> Result<String, SudokuError> {
+        let mut restarts = 0;
+        loop {
+            let mut board = BitBoard::new();
+
+            // set 1-9 randomly to nine of the cells
+            let mut valid = true;
+            for i in 0..9 {
+                let index = self.select_non_fixed(&board);
+                valid = valid && board.assign(index, i + 1);
+            }
+            valid = valid && constraint::propagate(&mut board, &self.constraints);
+
+            if valid {
+                self.try_init_game(&mut board);
+                let mut budget = RESTART_BUDGET;
+                // an unsolvable or too expensive initial game is simply replaced by another one
+                if self.try_solve_game(&mut board, &mut budget) == SolveOutcome::Solved {
+                    return Ok(board
+                        .to_game()
+                        .into_iter()
+                        .map(|item| item.to_string())
+                        .collect());
+                }
+            }
+            restarts += 1;
+            if !observer(Progress::Restart(restarts)) {
+                return Err(SudokuError::Cancelled);
+            }
         }
     }
-    if candidates.len() > 0 {
-        let random_idx = random_index(candidates.len() as u8);
-        return candidates[random_idx as usize];
+
+    /**
+     * Dig cells out of a game, usually a solution, while its solution stays unique. The game
+     * must have a unique solution itself.
+     */
+    pub fn dig_holes(&mut self, game: &str) -> Result<String, SudokuError> {
+        self.dig_holes_with(game, &mut |_| true)
     }
 
-    return 81;
-}
+    /**
+     * Like `dig_holes`, reporting every cell dug out to `observer`. Digging stops with
+     * `SudokuError::Cancelled` as soon as the observer returns false.
+     */
+    pub fn dig_holes_with(
+        &mut self,
+        game: &str,
+        observer: &mut dyn FnMut(Progress) -> bool,
+    ) -> Result<String, SudokuError> {
+        let mut game_vec = game_str_to_vec(game)?;
+        self.check_unique(&game_vec)?;
+        let remain_cells = MIN_CELLS + self.random_index(6);
+        let mut can_dig_cells = [true; 81];
+        let mut total = 81;
+
+        while total > remain_cells {
+            let start = self.next_diggable_index(&can_dig_cells);
+            if start > 80 {
+                break;
+            }
 
-fn random_index(total: u8) -> u8 {
-    rand::random::<u8>() % total
-}
+            if is_game_has_unique_solution(&mut game_vec, start, &self.constraints) {
+                game_vec[start] = 0;
+                total -= 1;
+                if !observer(Progress::Dug(81 - total as usize)) {
+                    return Err(SudokuError::Cancelled);
+                }
+            }
+            can_dig_cells[start] = false;
+        }
+        Ok(game_vec.into_iter().map(|item| item.to_string()).collect())
+    }
 
-/**
- * In one trail of digging a hole, suppose we try to dig a cell filled with the digit 6, then
- * Step 1: substitute the digit 6 into another new one from 1 through 9 one by one
- * excluding 6 while meeting the game rules;
- * Step 2: call the solver to solve the puzzle with the givens including the new digit.
- * Step 3: once the solver reports a solution, terminate the solver and claim that the
- * puzzle generated by digging out the digit 6 into empty cell has two solutions at least
- * because originally a solution exists when the cell is filled with the digit 6.
- * Step 4: only if all rest 8 digits excluding 6 are used to do such a trial in step 1 and 2
- * and the solver reports none solution, it is safe to claim that the puzzle generated by
- * digging out the digit 6 into empty cell has a unique solution, which means that the
- * operation of digging out the digit 6 is feasible and legal.
- */
-fn is_game_has_unique_solution(game_vec: &mut Vec<u8>, index: usize) -> bool {
-    let cell_value = game_vec[index];
+    /**
+     * Generate a puzzle graded as `difficulty` together with its actual grade.
+     * Cells of a new solution are dug one by one while the puzzle stays unique, and a cell whose
+     * removal would make the puzzle harder than requested is put back. Puzzles that end up too
+     * easy are retried with another solution for a while, then the puzzle whose grade was the
+     * closest to the requested one is returned.
+     */
+    pub fn generate_puzzle(&mut self, difficulty: Difficulty) -> (String, Difficulty) {
+        self.generate_closest_puzzle(difficulty, DEFAULT_TIME_BUDGET)
+    }
 
-    for value in 1..10 {
-        if value == cell_value {
-            continue;
+    /**
+     * Like `generate_puzzle`, with no other grade than `difficulty` accepted: the search fails
+     * with `SudokuError::Timeout` if it does not find one within `budget`. See
+     * `generate_closest_puzzle` to get the best puzzle found instead.
+     */
+    pub fn generate_puzzle_with_budget(
+        &mut self,
+        difficulty: Difficulty,
+        budget: Duration,
+    ) -> Result<String, SudokuError> {
+        match self.generate_closest_puzzle(difficulty, budget) {
+            (puzzle, current) if current == difficulty => Ok(puzzle),
+            _ => Err(SudokuError::Timeout),
         }
-        game_vec[index] = value;
-        if Board::is_valid_game(game_vec) {
-            let mut board = Board::new();
-            if let Ok(_) = board.init(game_vec) {
-                let (solved, _) = try_solve_game(&mut board);
-                if solved {
-                    game_vec[index] = cell_value; // restore
-                    return false;
+    }
+
+    /**
+     * Like `generate_puzzle`, searching for `budget`: once it runs out, the puzzle whose grade
+     * was the closest to `difficulty` is returned with its grade.
+     */
+    pub fn generate_closest_puzzle(
+        &mut self,
+        difficulty: Difficulty,
+        budget: Duration,
+    ) -> (String, Difficulty) {
+        let deadline = Instant::now() + budget;
+        let mut best: Option<(Vec<u8>, Difficulty)> = None;
+
+        loop {
+            let game = self.generate_game();
+            let mut game_vec = game_str_to_vec(&game).unwrap();
+            let mut can_dig_cells = [true; 81];
+            let mut current = Difficulty::Easy;
+
+            while Instant::now() < deadline {
+                let start = self.next_diggable_index(&can_dig_cells);
+                if start > 80 {
+                    break;
+                }
+                can_dig_cells[start] = false;
+
+                if !is_game_has_unique_solution(&mut game_vec, start, &self.constraints) {
+                    continue;
+                }
+                let value = game_vec[start];
+                game_vec[start] = 0;
+                match grade(&game_vec) {
+                    Ok(dug) if dug <= difficulty => current = dug,
+                    // too hard, or only unique with the extra rules, keep the digit
+                    _ => game_vec[start] = value,
                 }
             }
+
+            let distance = |d: Difficulty| (d as i8 - difficulty as i8).abs();
+            if best
+                .as_ref()
+                .is_none_or(|(_, d)| distance(current) < distance(*d))
+            {
+                best = Some((game_vec, current));
+            }
+            if current == difficulty || Instant::now() >= deadline {
+                break;
+            }
         }
+
+        let (game_vec, current) = best.unwrap();
+        let puzzle = game_vec.into_iter().map(|item| item.to_string()).collect();
+        (puzzle, current)
     }
-    true
-}
 
-fn select_non_fixed(board: &Board) -> u8 {
-    loop {
-        let next_id = random_index(81);
-        let cell = &board.cells[next_id as usize];
-        if cell.is_fixed() {
-            continue;
+    /**
+     * A killer puzzle and its cages. A new solution is split into random cages of two or more
+     * connected cells without a repeated digit, then its givens are dropped in random order
+     * while the puzzle stays unique under the cages, which usually leaves none or very few of
+     * them.
+     */
+    pub fn generate_killer(&mut self) -> (String, Vec<Cage>) {
+        let mut game_vec = game_str_to_vec(&self.generate_game()).unwrap();
+        let cages = self.partition_cages(&game_vec);
+        let mut constraints = self.constraints.clone();
+        constraints.extend(killer::constraints(&cages));
+
+        let mut order: Vec<usize> = (0..81).collect();
+        order.shuffle(&mut self.rng);
+        for index in order {
+            if is_game_has_unique_solution(&mut game_vec, index, &constraints) {
+                game_vec[index] = 0;
+            }
         }
-        return next_id;
+        let puzzle = game_vec.into_iter().map(|item| item.to_string()).collect();
+        (puzzle, cages)
     }
-}
 
-/**
- * Reference
- * Finally, we find that randomly creating a puzzle with 11 givens can help to minimize
- * the computational time and meanwhile enhance the diversity of the generated
- * puzzles.
- */
-fn try_init_game(board: &mut Board) {
-    let mut count = 0;
-    loop {
-        let index: u8 = select_non_fixed(&board);
-        let cell = &board.cells[index as usize];
-        let mut candidates = cell.collect_candidates();
-        let mut rng = thread_rng();
-        candidates.shuffle(&mut rng);
+    // split a solution into cages grown from random cells, each digit at most once per cage
+    fn partition_cages(&mut self, solution: &[u8]) -> Vec<Cage> {
+        'partition: loop {
+            let mut cage_of = [usize::MAX; 81];
+            let mut cages: Vec<Vec<usize>> = Vec::new();
+            let mut order: Vec<usize> = (0..81).collect();
+            order.shuffle(&mut self.rng);
+
+            for start in order {
+                if cage_of[start] != usize::MAX {
+                    continue;
+                }
+                let id = cages.len();
+                let size = self.rng.gen_range(2..=KILLER_CAGE_SIZE);
+                let mut cells = vec![start];
+                cage_of[start] = id;
+                while cells.len() < size {
+                    let next: Vec<usize> = cells
+                        .iter()
+                        .flat_map(|&cell| neighbours(cell, 9))
+                        .filter(|&n| {
+                            cage_of[n] == usize::MAX
+                                && cells.iter().all(|&c| solution[c] != solution[n])
+                        })
+                        .collect();
+                    match next.choose(&mut self.rng) {
+                        Some(&n) => {
+                            cage_of[n] = id;
+                            cells.push(n);
+                        }
+                        None => break,
+                    }
+                }
+                cages.push(cells);
+            }
+
+            // a lone cell would be a given, it joins a neighbouring cage without its digit, and
+            // the solution is split again if there is none
+            for id in 0..cages.len() {
+                if cages[id].len() != 1 {
+                    continue;
+                }
+                let cell = cages[id][0];
+                let joined = neighbours(cell, 9).map(|n| cage_of[n]).find(|&other| {
+                    other != id && cages[other].iter().all(|&c| solution[c] != solution[cell])
+                });
+                let Some(other) = joined else {
+                    continue 'partition;
+                };
+                cages[id].clear();
+                cages[other].push(cell);
+                cage_of[cell] = other;
+            }
 
-        let board_state = board.backup();
+            return cages
+                .into_iter()
+                .filter(|cells| !cells.is_empty())
+                .map(|cells| {
+                    let sum = cells.iter().map(|&cell| solution[cell]).sum();
+                    // the sum of digits of a solution is always a possible sum
+                    Cage::new(cells.into_iter().map(|cell| cell as u8).collect(), sum).unwrap()
+                })
+                .collect();
+        }
+    }
 
-        for c in &candidates {
-            let assign_result = board.assign_cell(index, *c);
-            if assign_result {
-                count += 1;
+    /**
+     * A random solution grid of any geometry, the counterpart of `generate_game` for the grids
+     * that are not 9x9. `SudokuError::NoSolution` if the regions of a jigsaw layout cannot be
+     * filled.
+     */
+    pub fn generate_grid(&mut self, geometry: &Geometry) -> Result<Grid, SudokuError> {
+        let empty = Grid::new(geometry.clone());
+        let state = GridState::new(geometry, empty.values()).unwrap();
+        for restart in 0..=GRID_RESTARTS {
+            let mut solution = None;
+            let mut budget = match restart {
+                GRID_RESTARTS => usize::MAX,
+                _ => GRID_RESTART_BUDGET,
+            };
+            let rng = &mut self.rng;
+            state.search(
+                1,
+                &mut budget,
+                &mut |digits| digits.shuffle(rng),
+                &mut |values| solution = Some(values.to_vec()),
+            );
+            if let Some(values) = solution {
+                return Ok(empty.with_values(values));
+            }
+            // an unlucky search is started again with other random choices, one that did not
+            // use up its budget tried every grid
+            if budget > 0 {
                 break;
+            }
+        }
+        Err(SudokuError::NoSolution)
+    }
+
+    /**
+     * A random jigsaw layout of side `size` with at least one solution. The boxes of
+     * `Geometry::for_size`, or the rows for a prime side, are reshaped by swapping cells
+     * between neighbouring regions while every region stays connected, and a layout that
+     * cannot be filled is replaced by another one. None if the size is 0 or above `MAX_SIZE`.
+     * Fill it with `generate_grid` and dig it with `dig_grid`.
+     */
+    pub fn generate_jigsaw(&mut self, size: usize) -> Option<Geometry> {
+        if size == 0 || size > MAX_SIZE {
+            return None;
+        }
+        let start: Vec<u8> = match Geometry::for_size(size) {
+            Some(geometry) => geometry.regions().to_vec(),
+            None => (0..size * size).map(|i| (i / size) as u8).collect(),
+        };
+        loop {
+            let mut regions = start.clone();
+            self.reshape_regions(&mut regions, size);
+            let geometry = Geometry::from_regions(regions).unwrap();
+            let state = GridState::new(&geometry, &vec![0; geometry.cells()]).unwrap();
+            let mut budget = GRID_RESTART_BUDGET;
+            let rng = &mut self.rng;
+            let order = &mut |digits: &mut [u8]| digits.shuffle(rng);
+            if state.search(1, &mut budget, order, &mut |_| {}) == 1 {
+                return Some(geometry);
+            }
+        }
+    }
+
+    // move cells across region borders, each move giving a cell back so the sizes stay equal
+    fn reshape_regions(&mut self, regions: &mut [u8], size: usize) {
+        let cells = regions.len();
+        let mut swaps = 0;
+        for _ in 0..100 * cells {
+            if swaps >= JIGSAW_SWAPS * cells {
+                break;
+            }
+            let a = self.rng.gen_range(0..cells);
+            let b = match neighbours(a, size)
+                .collect::<Vec<_>>()
+                .choose(&mut self.rng)
+            {
+                Some(&b) => b,
+                None => break,
+            };
+            let (from, to) = (regions[a], regions[b]);
+            if from == to {
+                continue;
+            }
+            // `a` joins the region of `b`, which gives one of its cells bordering `a`'s back
+            let givers: Vec<usize> = (0..cells)
+                .filter(|&c| regions[c] == to && neighbours(c, size).any(|n| regions[n] == from))
+                .collect();
+            let c = *givers.choose(&mut self.rng).unwrap();
+            regions[a] = to;
+            regions[c] = from;
+            if is_connected(regions, size, from) && is_connected(regions, size, to) {
+                swaps += 1;
             } else {
-                board.restore(&board_state);
+                regions[a] = from;
+                regions[c] = to;
             }
         }
-        if count >= 2 {
-            break;
+    }
+
+    /**
+     * Dig cells out of a grid, usually a solution, in random order while its solution stays
+     * unique. The grid must have a unique solution itself.
+     */
+    pub fn dig_grid(&mut self, grid: &Grid) -> Result<Grid, SudokuError> {
+        grid.unique_solution()?;
+        let geometry = grid.geometry();
+        let mut values = grid.values().to_vec();
+        let mut order: Vec<usize> = (0..values.len()).filter(|&i| values[i] > 0).collect();
+        order.shuffle(&mut self.rng);
+
+        for index in order {
+            let value = values[index];
+            values[index] = 0;
+            // unique if no solution is left once the dug value is ruled out
+            let unique = match GridState::new(geometry, &values) {
+                Some(mut state) => {
+                    let mut budget = GRID_DIG_BUDGET;
+                    !state.eliminate(index, value)
+                        || (state.search(1, &mut budget, &mut |_| {}, &mut |_| {}) == 0
+                            && budget > 0)
+                }
+                None => false,
+            };
+            if !unique {
+                values[index] = value;
+            }
         }
+        Ok(grid.with_values(values))
     }
-}
 
-fn try_solve_game(board: &mut Board) -> (bool, usize) {
-    if board.is_solved() {
-        return (true, 0);
+    // the game must have exactly one solution, under the extra rules if there are any
+    fn check_unique(&self, game: &[u8]) -> Result<(), SudokuError> {
+        if self.constraints.is_empty() {
+            return unique_solution(game).map(|_| ());
+        }
+        check_game(game)?;
+        constraint::check(game, &self.constraints)?;
+        let count = BitBoard::from_game(game).map_or(0, |board| {
+            constraint::search(&board, &self.constraints, 2, &mut |_| {})
+        });
+        match count {
+            0 => Err(SudokuError::NoSolution),
+            1 => Ok(()),
+            _ => Err(SudokuError::MultipleSolutions),
+        }
     }
 
-    let (index, mut candidates) = board.next_candidate_cell();
-    let mut rng = thread_rng();
-    candidates.shuffle(&mut rng);
-    let board_state = board.backup();
-    let mut steps: usize = 0;
+    fn next_diggable_index(&mut self, diggable_cells: &[bool; 81]) -> usize {
+        let candidates: Vec<usize> = (0..81).filter(|&index| diggable_cells[index]).collect();
+        if !candidates.is_empty() {
+            let random_idx = self.random_index(candidates.len() as u8);
+            return candidates[random_idx as usize];
+        }
+
+        81
+    }
 
-    for c in candidates {
-        steps += 1;
-        if steps > 100 {
-            // too many steps, try another solution
-            return (false, steps);
+    fn random_index(&mut self, total: u8) -> u8 {
+        self.rng.gen_range(0..total)
+    }
+
+    fn select_non_fixed(&mut self, board: &BitBoard) -> u8 {
+        loop {
+            let next_id = self.random_index(81);
+            if board.value(next_id) > 0 {
+                continue;
+            }
+            return next_id;
         }
+    }
 
-        let assign_result = board.assign_cell(index, c);
-        if assign_result {
-            // assign succeed, continue to solve the game
-            let (solve_result, solve_steps) = try_solve_game(board);
-            steps += solve_steps;
+    /**
+     * Reference
+     * Finally, we find that randomly creating a puzzle with 11 givens can help to minimize
+     * the computational time and meanwhile enhance the diversity of the generated
+     * puzzles.
+     */
+    fn try_init_game(&mut self, board: &mut BitBoard) {
+        let mut count = 0;
+        while count < 2 && !board.is_solved() {
+            let index: u8 = self.select_non_fixed(board);
+            let mut candidates = digits(board.cell_candidates(index));
+            candidates.shuffle(&mut self.rng);
+
+            for c in &candidates {
+                let mut next = *board;
+                if next.assign(index, *c) && constraint::propagate(&mut next, &self.constraints) {
+                    *board = next;
+                    count += 1;
+                    break;
+                }
+            }
+        }
+    }
 
-            if solve_result {
-                return (true, steps);
+    /**
+     * Search a solution with randomly ordered candidates. Each candidate attempt consumes one
+     * unit of `budget`, and the search is abandoned with `OutOfBudget` once it is used up, which
+     * is never mistaken for a proof that the game has no solution.
+     */
+    fn try_solve_game(&mut self, board: &mut BitBoard, budget: &mut usize) -> SolveOutcome {
+        let (index, candidates) = match board.next_cell() {
+            Some(cell) => cell,
+            None if constraint::check(&board.to_game(), &self.constraints).is_ok() => {
+                return SolveOutcome::Solved
+            }
+            None => return SolveOutcome::Unsolvable,
+        };
+        let mut candidates = digits(candidates);
+        candidates.shuffle(&mut self.rng);
+
+        for c in candidates {
+            if *budget == 0 {
+                return SolveOutcome::OutOfBudget;
+            }
+            *budget -= 1;
+
+            // the board is copied so a failed candidate leaves it untouched
+            let mut next = *board;
+            if next.assign(index, c) && constraint::propagate(&mut next, &self.constraints) {
+                // assign succeed, continue to solve the game
+                match self.try_solve_game(&mut next, budget) {
+                    SolveOutcome::Unsolvable => {}
+                    outcome => {
+                        *board = next;
+                        return outcome;
+                    }
+                }
             }
         }
-        // current candidate failed
-        // restore the board and test next candidate
-        board.restore(&board_state);
+
+        // all candidates failed
+        SolveOutcome::Unsolvable
     }
+}
 
-    // all candidates failed
-    (false, steps)
+pub fn generate_game() -> String {
+    Generator::new(thread_rng()).generate_game()
+}
+
+pub fn dig_holes(game: &str) -> Result<String, SudokuError> {
+    Generator::new(thread_rng()).dig_holes(game)
+}
+
+pub fn generate_puzzle(difficulty: Difficulty) -> (String, Difficulty) {
+    Generator::new(thread_rng()).generate_puzzle(difficulty)
+}
+
+/**
+ * Digging out the cell at `index` is legal only if the remaining puzzle still has exactly one
+ * solution. The puzzle is known to be solved with the dug digit, so it is unique when a search
+ * on a `BitBoard` finds no solution with any other digit in that cell, under the extra rules
+ * if there are any. The cell is restored if digging it would make the puzzle ambiguous.
+ */
+fn is_game_has_unique_solution(
+    game_vec: &mut [u8],
+    index: usize,
+    constraints: &[Arc<dyn Constraint>],
+) -> bool {
+    let cell_value = game_vec[index];
+    game_vec[index] = 0;
+
+    let unique = BitBoard::from_game(game_vec).is_some_and(|mut board| {
+        !board.eliminate(index as u8, cell_value)
+            || constraint::search(&board, constraints, 1, &mut |_| {}) == 0
+    });
+    game_vec[index] = cell_value; // restore
+    unique
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{dig_holes, generate_game, random_index, try_solve_game};
+    use super::{dig_holes, generate_game, Generator, Progress, SolveOutcome, MIN_CELLS};
+    use crate::bitboard::BitBoard;
     use crate::board::{game_str_to_vec, Board};
+    use crate::constraint::{AllDifferent, Constraint};
+    use crate::difficulty::{grade, Difficulty};
+    use crate::error::SudokuError;
+    use crate::grid::{Geometry, Grid};
+    use crate::killer;
+    use crate::parser::ParseMode;
+    use crate::variant::Variant;
     use rand::{self, prelude::SliceRandom, thread_rng};
+    use std::sync::Arc;
+    use std::time::Duration;
 
     #[test]
     fn test_random_index() {
-        println!("{}", random_index(81));
-        println!("{}", random_index(81));
-        println!("{}", random_index(81));
+        let mut generator = Generator::new(thread_rng());
+        for _ in 0..100 {
+            assert!(generator.random_index(81) < 81);
+        }
     }
 
     #[test]
@@ -210,24 +664,292 @@ mod tests {
     fn test_genera_new_game() {
         let g = generate_game();
         println!("{}", g);
-        let p = dig_holes(&g);
+        let p = dig_holes(&g).unwrap();
         println!("{}", p);
 
         let mut board = Board::new();
         let game_vec = game_str_to_vec(&p).unwrap();
         board.init(&game_vec).unwrap();
-        let success = board.solve();
-        assert_eq!(success, true);
+        assert_eq!(board.count_solutions(2), 1);
+        assert_eq!(board.solve(), Ok(()));
         println!("{}", board.serialize());
     }
 
+    #[test]
+    fn test_seeded_generator() {
+        let mut g1 = Generator::from_seed(12345);
+        let mut g2 = Generator::from_seed(12345);
+        let game = g1.generate_game();
+        assert_eq!(game, g2.generate_game());
+        assert_eq!(g1.dig_holes(&game), g2.dig_holes(&game));
+
+        let mut g3 = Generator::from_seed(54321);
+        assert_ne!(game, g3.generate_game());
+
+        // a seed names the same game on every platform and rand version
+        assert_eq!(
+            game,
+            "758462391214593867963718254892647513436185972571329486125974638689231745347856129"
+        );
+    }
+
+    #[test]
+    fn test_observer() {
+        let mut restarts = Vec::new();
+        let game = Generator::from_seed(2)
+            .generate_game_with(&mut |progress| {
+                restarts.push(progress);
+                true
+            })
+            .unwrap();
+        assert_eq!(game, Generator::from_seed(2).generate_game());
+        for (k, progress) in restarts.iter().enumerate() {
+            assert_eq!(*progress, Progress::Restart(k + 1));
+        }
+
+        // stopped after the third cell dug
+        let mut dug = 0;
+        let mut generator = Generator::from_seed(2);
+        let puzzle = generator.dig_holes_with(&game, &mut |_| {
+            dug += 1;
+            dug < 3
+        });
+        assert_eq!(puzzle, Err(SudokuError::Cancelled));
+        assert_eq!(dug, 3);
+    }
+
+    #[test]
+    fn test_dig_holes_errors() {
+        let mut generator = Generator::from_seed(2);
+        assert_eq!(
+            generator.dig_holes("12"),
+            Err(SudokuError::InvalidLength {
+                expected: 81,
+                found: 2
+            })
+        );
+        assert_eq!(
+            generator.dig_holes(&".".repeat(81)),
+            Err(SudokuError::MultipleSolutions)
+        );
+        let game = "11".to_string() + &".".repeat(79);
+        assert!(matches!(
+            generator.dig_holes(&game),
+            Err(SudokuError::Conflict {
+                cell: 1,
+                digit: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_generate_puzzle() {
+        let mut generator = Generator::from_seed(4);
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let (p, d) = generator.generate_puzzle(difficulty);
+            let game_vec = game_str_to_vec(&p).unwrap();
+            assert_eq!(d, difficulty);
+            assert_eq!(grade(&game_vec), Ok(difficulty));
+
+            let mut board = Board::new();
+            board.init(&game_vec).unwrap();
+            assert_eq!(board.count_solutions(2), 1);
+        }
+    }
+
+    #[test]
+    fn test_generate_puzzle_budget() {
+        // no time to dig anything, the solution itself is the best puzzle found
+        let mut generator = Generator::from_seed(4);
+        let (p, d) = generator.generate_closest_puzzle(Difficulty::Extreme, Duration::ZERO);
+        assert_eq!(d, Difficulty::Easy);
+        assert!(!p.contains('0'));
+
+        let p = generator.generate_puzzle_with_budget(Difficulty::Extreme, Duration::ZERO);
+        assert_eq!(p, Err(SudokuError::Timeout));
+        let p = generator.generate_puzzle_with_budget(Difficulty::Easy, Duration::from_secs(10));
+        assert_eq!(
+            grade(&game_str_to_vec(&p.unwrap()).unwrap()),
+            Ok(Difficulty::Easy)
+        );
+    }
+
     #[test]
     fn test_try_solve() {
-        let game = ".2..194..5.9423618.137865.9..7932146.62147.95194.6.2737.1.549628356.17..94.278351";
+        // r1c2 is 1 in the only solution without the wrong 9
+        let game =
+            "49....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
         let vec = game_str_to_vec(game).unwrap();
+        let mut board = BitBoard::from_game(&vec).unwrap();
+        let mut generator = Generator::from_seed(1);
+        let mut budget = usize::MAX;
+        assert_eq!(
+            generator.try_solve_game(&mut board, &mut budget),
+            SolveOutcome::Unsolvable
+        );
+    }
+
+    #[test]
+    fn test_try_solve_budget() {
+        let mut board = BitBoard::new();
+        let mut generator = Generator::from_seed(1);
+        let mut budget = 0;
+        assert_eq!(
+            generator.try_solve_game(&mut board, &mut budget),
+            SolveOutcome::OutOfBudget
+        );
+
+        let mut budget = usize::MAX;
+        assert_eq!(
+            generator.try_solve_game(&mut board, &mut budget),
+            SolveOutcome::Solved
+        );
+        assert!(board.is_solved());
+        assert!(Board::is_valid_game(&board.to_game()));
+    }
+
+    #[test]
+    fn test_constraints() {
+        let diagonal = AllDifferent::new((0..9).map(|i| i * 10).collect()).unwrap();
+        let constraints: Vec<Arc<dyn Constraint>> = vec![Arc::new(diagonal.clone())];
+        let mut generator = Generator::from_seed(7).with_constraints(constraints.clone());
+        let game = generator.generate_game();
+        assert!(diagonal.check(&game_str_to_vec(&game).unwrap()));
+
+        let puzzle = generator.dig_holes(&game).unwrap();
+        let mut board = Board::new();
+        board.add_constraint(Arc::new(diagonal));
+        board.init(&game_str_to_vec(&puzzle).unwrap()).unwrap();
+        assert_eq!(board.count_solutions(2), 1);
+        board.solve().unwrap();
+        assert_eq!(board.serialize(), game);
+    }
+
+    #[test]
+    fn test_variants() {
+        let variants = [Variant::Diagonal, Variant::Windoku];
+        let mut generator = Generator::from_seed(23).with_variants(&variants);
+        let game = generator.generate_game();
+        let solution = game_str_to_vec(&game).unwrap();
+        assert!(Board::is_valid_variant_game(&solution, &variants));
+
+        let puzzle = generator.dig_holes(&game).unwrap();
+        let mut board = Board::new();
+        for variant in variants {
+            board.add_variant(variant);
+        }
+        board.init(&game_str_to_vec(&puzzle).unwrap()).unwrap();
+        assert_eq!(board.count_solutions(2), 1);
+        board.solve().unwrap();
+        assert_eq!(board.serialize(), game);
+    }
+
+    #[test]
+    fn test_generate_killer() {
+        let mut generator = Generator::from_seed(25);
+        let (puzzle, cages) = generator.generate_killer();
+        let mut covered = [0; 81];
+        for cage in &cages {
+            for &cell in cage.cells() {
+                covered[cell as usize] += 1;
+            }
+        }
+        assert!(covered.iter().all(|&count| count == 1));
+        // no cage is a given in disguise, whatever the split
+        assert!(cages.iter().all(|cage| cage.cells().len() > 1));
+        for seed in 0..20 {
+            let mut generator = Generator::from_seed(seed);
+            let solution = game_str_to_vec(&generator.generate_game()).unwrap();
+            let cages = generator.partition_cages(&solution);
+            assert!(cages.iter().all(|cage| cage.cells().len() > 1));
+        }
+
         let mut board = Board::new();
-        assert_eq!(Ok(true), board.init(&vec));
-        let (solved, _) = try_solve_game(&mut board);
-        assert_eq!(solved, false);
+        for cage in killer::constraints(&cages) {
+            board.add_constraint(cage);
+        }
+        let game_vec = game_str_to_vec(&puzzle).unwrap();
+        assert!(game_vec.iter().filter(|&&value| value > 0).count() < MIN_CELLS as usize);
+        board.init(&game_vec).unwrap();
+        assert_eq!(board.count_solutions(2), 1);
+        board.solve().unwrap();
+        // solved under the cages too
+        assert!(board.is_solved());
+    }
+
+    #[test]
+    fn test_generate_grid() {
+        for size in [4, 6, 9, 12, 16] {
+            let geometry = Geometry::for_size(size).unwrap();
+            let mut generator = Generator::from_seed(size as u64);
+            let solution = generator.generate_grid(&geometry).unwrap();
+            assert!(solution.is_solved());
+
+            let puzzle = generator.dig_grid(&solution).unwrap();
+            assert!(puzzle.clues() < geometry.cells());
+            assert_eq!(puzzle.unique_solution(), Ok(solution.clone()));
+
+            let read = Grid::parse(geometry.clone(), &puzzle.serialize(), ParseMode::Strict);
+            assert_eq!(read, Ok(puzzle.clone()));
+            let read = Grid::parse(geometry, &puzzle.to_string(), ParseMode::Lenient);
+            assert_eq!(read, Ok(puzzle));
+
+            let mut again = Generator::from_seed(size as u64);
+            assert_eq!(again.generate_grid(solution.geometry()), Ok(solution));
+        }
+
+        // a connected layout whose regions cannot all hold every digit
+        let unfillable = Geometry::parse_regions("1112132233423444").unwrap();
+        assert_eq!(
+            Generator::from_seed(4).generate_grid(&unfillable),
+            Err(SudokuError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn test_generate_large_grid() {
+        // digging a 25x25 grid takes too long for a debug build, filling one does not
+        let geometry = Geometry::for_size(25).unwrap();
+        let solution = Generator::from_seed(25).generate_grid(&geometry).unwrap();
+        assert!(solution.is_solved());
+        let serialized = solution.serialize();
+        assert!(serialized.contains('P'));
+        let read = Grid::parse(geometry, &serialized, ParseMode::Strict);
+        assert_eq!(read, Ok(solution));
+    }
+
+    #[test]
+    fn test_generate_jigsaw() {
+        for size in [5, 6, 9] {
+            let mut generator = Generator::from_seed(24);
+            let geometry = generator.generate_jigsaw(size).unwrap();
+            assert_eq!(geometry.boxes(), None);
+            assert_eq!(geometry.size(), size);
+            let start = Geometry::for_size(size).map(|boxes| boxes.regions().to_vec());
+            assert_ne!(Some(geometry.regions().to_vec()), start);
+            // a valid layout reads back
+            let read = Geometry::parse_regions(&geometry.write_regions());
+            assert_eq!(read, Some(geometry.clone()));
+
+            let solution = generator.generate_grid(&geometry).unwrap();
+            assert!(solution.is_solved());
+            let puzzle = generator.dig_grid(&solution).unwrap();
+            assert_eq!(puzzle.unique_solution(), Ok(solution));
+
+            let mut again = Generator::from_seed(24);
+            assert_eq!(again.generate_jigsaw(size), Some(geometry));
+        }
+        assert_eq!(Generator::from_seed(24).generate_jigsaw(0), None);
+    }
+
+    #[test]
+    fn test_dig_grid_errors() {
+        let geometry = Geometry::for_size(6).unwrap();
+        let mut generator = Generator::from_seed(6);
+        assert_eq!(
+            generator.dig_grid(&Grid::new(geometry)).err(),
+            Some(SudokuError::MultipleSolutions)
+        );
     }
 }
@@ -0,0 +1,308 @@
+/**
+ * Largest supported grid side. Candidates are kept as one bit per digit in a `u32`, and digits
+ * above 9 are written as letters from `A`.
+ */
+pub const MAX_SIZE: usize = 32;
+
+/**
+ * The shape of a grid: it is `size` cells on each side, holding the digits 1 to `size`, and
+ * split into `size` regions of `size` cells. The regions are usually boxes `box_rows` rows
+ * high and `box_cols` columns wide, or any connected shapes for a jigsaw grid.
+ * Units are numbered like the classic board's: rows first, then columns, then regions.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Geometry {
+    size: usize,
+    // the box shape as rows and columns, None for irregular regions
+    boxes: Option<(usize, usize)>,
+    // the region of each cell, numbered from 0 like the units
+    regions: Vec<u8>,
+    units: Vec<Vec<usize>>,
+    cell_units: Vec<[usize; 3]>,
+    peers: Vec<Vec<usize>>,
+}
+
+impl Geometry {
+    /**
+     * A grid made of boxes of `box_rows` by `box_cols` cells, None if either is 0 or the grid
+     * would be larger than `MAX_SIZE`.
+     */
+    pub fn new(box_rows: usize, box_cols: usize) -> Option<Self> {
+        let size = box_rows * box_cols;
+        if size == 0 || size > MAX_SIZE {
+            return None;
+        }
+        let regions = (0..size * size)
+            .map(|i| ((i / size / box_rows) * box_rows + (i % size) / box_cols) as u8)
+            .collect();
+        Some(Geometry::build(size, Some((box_rows, box_cols)), regions))
+    }
+
+    /**
+     * A jigsaw grid: `regions` gives the region of each cell, from 0, for a square grid. None
+     * unless every region has as many cells as a row and they are all connected.
+     */
+    pub fn from_regions(regions: Vec<u8>) -> Option<Self> {
+        let size = (1..=MAX_SIZE).find(|size| size * size >= regions.len())?;
+        if size * size != regions.len() {
+            return None;
+        }
+        let mut counts = vec![0; size];
+        for &region in &regions {
+            *counts.get_mut(region as usize)? += 1;
+        }
+        if counts.iter().any(|&count| count != size)
+            || !(0..size as u8).all(|region| is_connected(&regions, size, region))
+        {
+            return None;
+        }
+        Some(Geometry::build(size, None, regions))
+    }
+
+    /**
+     * Read a region map written one character per cell, the regions numbered from 1 like the
+     * digits, such as `111222333` for the first row of a 9x9 grid. Whitespace is skipped.
+     */
+    pub fn parse_regions(input: &str) -> Option<Self> {
+        let regions = input
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| char_value(c).map(|value| value - 1))
+            .collect::<Option<Vec<u8>>>()?;
+        Geometry::from_regions(regions)
+    }
+
+    // the region map as read by `parse_regions`
+    pub fn write_regions(&self) -> String {
+        self.regions
+            .iter()
+            .map(|&region| self.digit_char(region + 1))
+            .collect()
+    }
+
+    fn build(size: usize, boxes: Option<(usize, usize)>, regions: Vec<u8>) -> Self {
+        let cells = size * size;
+        let mut units = vec![Vec::with_capacity(size); 3 * size];
+        let mut cell_units = Vec::with_capacity(cells);
+        for (i, &region) in regions.iter().enumerate() {
+            let (row, col) = (i / size, i % size);
+            let ids = [row, size + col, 2 * size + region as usize];
+            for id in ids {
+                units[id].push(i);
+            }
+            cell_units.push(ids);
+        }
+
+        let peers = (0..cells)
+            .map(|i| {
+                let mut peers: Vec<usize> = cell_units[i]
+                    .iter()
+                    .flat_map(|&unit| units[unit].iter().copied())
+                    .filter(|&peer| peer != i)
+                    .collect();
+                peers.sort_unstable();
+                peers.dedup();
+                peers
+            })
+            .collect();
+
+        Geometry {
+            size,
+            boxes,
+            regions,
+            units,
+            cell_units,
+            peers,
+        }
+    }
+
+    /**
+     * The usual shape for a grid of side `size`: boxes as square as possible, wider than high,
+     * such as 2x3 for 6x6 and 3x4 for 12x12. None for a prime side other than 1, which could
+     * only have boxes as long as a row.
+     */
+    pub fn for_size(size: usize) -> Option<Self> {
+        let box_rows = (1..=size)
+            .take_while(|rows| rows * rows <= size)
+            .filter(|rows| size.is_multiple_of(*rows))
+            .last()?;
+        if box_rows == 1 && size > 1 {
+            return None;
+        }
+        Geometry::new(box_rows, size / box_rows)
+    }
+
+    // the 9x9 grid of 3x3 boxes
+    pub fn classic() -> Self {
+        Geometry::new(3, 3).unwrap()
+    }
+
+    // the rows and columns of a box, None for a jigsaw grid
+    pub fn boxes(&self) -> Option<(usize, usize)> {
+        self.boxes
+    }
+
+    pub fn regions(&self) -> &[u8] {
+        &self.regions
+    }
+
+    // the number of cells on each side, and the largest digit
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn cells(&self) -> usize {
+        self.size * self.size
+    }
+
+    // the cells of every unit, rows, columns then boxes
+    pub fn units(&self) -> &[Vec<usize>] {
+        &self.units
+    }
+
+    // the row, column and box of a cell
+    pub fn cell_units(&self, index: usize) -> [usize; 3] {
+        self.cell_units[index]
+    }
+
+    // the other cells sharing a unit with a cell
+    pub fn peers(&self, index: usize) -> &[usize] {
+        &self.peers[index]
+    }
+
+    // a mask with the bit (c - 1) of every digit c
+    pub fn all_digits(&self) -> u32 {
+        u32::MAX >> (32 - self.size)
+    }
+
+    // the character of a digit, `.` for 0 and letters from `A` for the digits above 9
+    pub fn digit_char(&self, value: u8) -> char {
+        match value {
+            0 => '.',
+            1..=9 => (b'0' + value) as char,
+            _ => (b'A' + value - 10) as char,
+        }
+    }
+
+    // the digit written as `c` in this geometry, letters in either case
+    pub fn char_digit(&self, c: char) -> Option<u8> {
+        char_value(c).filter(|&value| value as usize <= self.size)
+    }
+}
+
+fn char_value(c: char) -> Option<u8> {
+    match c {
+        '1'..='9' => Some(c as u8 - b'0'),
+        'A'..='Z' => Some(c as u8 - b'A' + 10),
+        'a'..='z' => Some(c as u8 - b'a' + 10),
+        _ => None,
+    }
+}
+
+// the cells above, below, left and right of a cell
+pub(crate) fn neighbours(index: usize, size: usize) -> impl Iterator<Item = usize> {
+    let (row, col) = (index / size, index % size);
+    [
+        (row > 0).then(|| index - size),
+        (row + 1 < size).then(|| index + size),
+        (col > 0).then(|| index - 1),
+        (col + 1 < size).then(|| index + 1),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+// true if the cells of a region can all be reached from each other through side neighbours
+pub(crate) fn is_connected(regions: &[u8], size: usize, region: u8) -> bool {
+    let Some(start) = regions.iter().position(|&r| r == region) else {
+        return true;
+    };
+    let mut seen = vec![false; regions.len()];
+    let mut stack = vec![start];
+    seen[start] = true;
+    let mut reached = 0;
+    while let Some(i) = stack.pop() {
+        reached += 1;
+        for next in neighbours(i, size) {
+            if !seen[next] && regions[next] == region {
+                seen[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+    reached == regions.iter().filter(|&&r| r == region).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Geometry;
+
+    #[test]
+    fn test_for_size() {
+        let shapes: Vec<_> = [4, 6, 9, 12, 16, 25]
+            .iter()
+            .map(|&size| Geometry::for_size(size).unwrap().boxes().unwrap())
+            .collect();
+        assert_eq!(shapes, [(2, 2), (2, 3), (3, 3), (3, 4), (4, 4), (5, 5)]);
+        assert!(Geometry::for_size(7).is_none());
+        assert!(Geometry::for_size(36).is_none());
+        assert!(Geometry::new(0, 3).is_none());
+    }
+
+    #[test]
+    fn test_units() {
+        let geometry = Geometry::new(2, 3).unwrap();
+        assert_eq!(geometry.cells(), 36);
+        assert_eq!(geometry.units().len(), 18);
+        assert_eq!(geometry.units()[12], [0, 1, 2, 6, 7, 8]);
+        assert_eq!(geometry.cell_units(35), [5, 11, 17]);
+        // 5 in the row, 5 in the column and the 2 others of the box
+        assert_eq!(geometry.peers(0).len(), 12);
+        assert_eq!(geometry.all_digits(), 0x3f);
+
+        let classic = Geometry::classic();
+        assert_eq!(classic.peers(40).len(), 20);
+        assert_eq!(classic.cell_units(80), [8, 17, 26]);
+    }
+
+    #[test]
+    fn test_digits() {
+        let geometry = Geometry::for_size(16).unwrap();
+        assert_eq!(geometry.digit_char(0), '.');
+        assert_eq!(geometry.digit_char(9), '9');
+        assert_eq!(geometry.digit_char(10), 'A');
+        assert_eq!(geometry.digit_char(16), 'G');
+        assert_eq!(geometry.char_digit('g'), Some(16));
+        assert_eq!(geometry.char_digit('H'), None);
+        assert_eq!(geometry.char_digit('0'), None);
+        assert_eq!(Geometry::classic().char_digit('A'), None);
+    }
+
+    #[test]
+    fn test_regions() {
+        let classic = Geometry::classic();
+        assert_eq!(&classic.write_regions()[..18], "111222333111222333");
+        let read = Geometry::parse_regions(&classic.write_regions()).unwrap();
+        assert_eq!(read.boxes(), None);
+        assert_eq!(read.units(), classic.units());
+
+        // the first region takes r1c3 from the second, which takes r2c2 back
+        let layout = "
+            1112
+            1222
+            3344
+            3344";
+        let geometry = Geometry::parse_regions(layout).unwrap();
+        assert_eq!(geometry.size(), 4);
+        assert_eq!(geometry.regions()[4], 0);
+        assert_eq!(geometry.units()[8], [0, 1, 2, 4]);
+        assert_eq!(geometry.write_regions(), "1112122233443344");
+
+        // 3 and 4 are split in two
+        assert!(Geometry::parse_regions("1112122234433434").is_none());
+        // 1 has 5 cells
+        assert!(Geometry::parse_regions("1111122233443344").is_none());
+        assert!(Geometry::parse_regions("111212223344334").is_none());
+        assert!(Geometry::parse_regions("1112122233443345").is_none());
+    }
+}
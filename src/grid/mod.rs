@@ -0,0 +1,232 @@
+//! Grids of any size, described by a `Geometry`: 4x4, 6x6 with 2x3 boxes, 12x12 with 3x4 boxes,
+//! 16x16, 25x25 and so on. The classic 9x9 `Board` keeps its own fixed size types, which the
+//! TUI and the logic solver build on, while a `Grid` can be parsed, solved, generated (see
+//! `Generator::generate_grid`) and written at every size.
+
+mod geometry;
+pub(crate) mod search;
+
+pub(crate) use geometry::{is_connected, neighbours};
+pub use geometry::{Geometry, MAX_SIZE};
+
+use crate::error::SudokuError;
+use crate::parser::{parse_grid, ParseMode};
+use search::GridState;
+use std::fmt;
+use std::sync::Arc;
+
+/**
+ * The values of a grid, 0 for the empty cells, with the geometry they are laid out in. Clones
+ * share the geometry.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    geometry: Arc<Geometry>,
+    values: Vec<u8>,
+}
+
+impl Grid {
+    // an empty grid
+    pub fn new(geometry: Geometry) -> Self {
+        let cells = geometry.cells();
+        Grid {
+            geometry: Arc::new(geometry),
+            values: vec![0; cells],
+        }
+    }
+
+    /**
+     * Read a grid written one character per cell, see `parser::parse_grid`.
+     */
+    pub fn parse(geometry: Geometry, input: &str, mode: ParseMode) -> Result<Self, SudokuError> {
+        let values = parse_grid(input, mode, &geometry)?;
+        Ok(Grid {
+            geometry: Arc::new(geometry),
+            values,
+        })
+    }
+
+    // a grid of the same geometry with other values
+    pub(crate) fn with_values(&self, values: Vec<u8>) -> Self {
+        debug_assert_eq!(values.len(), self.geometry.cells());
+        Grid {
+            geometry: Arc::clone(&self.geometry),
+            values,
+        }
+    }
+
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    pub fn values(&self) -> &[u8] {
+        &self.values
+    }
+
+    pub fn value(&self, index: usize) -> u8 {
+        self.values[index]
+    }
+
+    /**
+     * Set a cell, 0 to empty it. Panics if the value is larger than the grid size.
+     */
+    pub fn set_value(&mut self, index: usize, value: u8) {
+        assert!(value as usize <= self.geometry.size());
+        self.values[index] = value;
+    }
+
+    // the number of non-empty cells
+    pub fn clues(&self) -> usize {
+        self.values.iter().filter(|&&value| value > 0).count()
+    }
+
+    // true if no digit appears twice in a unit
+    pub fn is_valid(&self) -> bool {
+        self.geometry.units().iter().all(|unit| {
+            let mut seen = 0u32;
+            unit.iter().all(|&index| match self.values[index] {
+                0 => true,
+                value => {
+                    let bit = 1 << (value - 1);
+                    let fresh = seen & bit == 0;
+                    seen |= bit;
+                    fresh
+                }
+            })
+        })
+    }
+
+    pub fn is_solved(&self) -> bool {
+        !self.values.contains(&0) && self.is_valid()
+    }
+
+    // like `Board::count_solutions`, a result equal to `limit` means at least `limit` solutions
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.search(limit, &mut |_| {})
+    }
+
+    /**
+     * Fill the grid with a solution. Givens that contradict each other are reported as
+     * `SudokuError::NoSolution` like any other unsolvable grid.
+     */
+    pub fn solve(&mut self) -> Result<(), SudokuError> {
+        let mut solution = None;
+        self.search(1, &mut |values| solution = Some(values.to_vec()));
+        self.values = solution.ok_or(SudokuError::NoSolution)?;
+        Ok(())
+    }
+
+    /**
+     * The solution of a grid that has exactly one, `SudokuError::MultipleSolutions` otherwise.
+     */
+    pub fn unique_solution(&self) -> Result<Grid, SudokuError> {
+        let mut solution = None;
+        match self.search(2, &mut |values| solution = Some(values.to_vec())) {
+            0 => Err(SudokuError::NoSolution),
+            1 => Ok(self.with_values(solution.unwrap())),
+            _ => Err(SudokuError::MultipleSolutions),
+        }
+    }
+
+    fn search(&self, limit: usize, found: &mut dyn FnMut(&[u8])) -> usize {
+        match GridState::new(&self.geometry, &self.values) {
+            Some(state) => {
+                let mut budget = usize::MAX;
+                state.search(limit, &mut budget, &mut |_| {}, found)
+            }
+            None => 0,
+        }
+    }
+
+    // one character per cell, `.` for the empty ones, which `parse` reads back
+    pub fn serialize(&self) -> String {
+        self.values
+            .iter()
+            .map(|&value| self.geometry.digit_char(value))
+            .collect()
+    }
+}
+
+// the grid drawn with its boxes, which `parse` reads back in lenient mode
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let geometry = &self.geometry;
+        let size = geometry.size();
+        // a jigsaw grid is drawn without separators
+        let (box_rows, box_cols) = geometry.boxes().unwrap_or((size, size));
+        let boxes = size / box_cols;
+        // each box is as wide as its digits and the spaces around them, like `---+-----+---`
+        let separator = (0..boxes)
+            .map(|b| "-".repeat(2 * box_cols - 1 + (b > 0) as usize + (b + 1 < boxes) as usize))
+            .collect::<Vec<_>>()
+            .join("+");
+        for row in 0..size {
+            if row > 0 && row % box_rows == 0 {
+                writeln!(f, "{separator}")?;
+            }
+            let mut line = String::new();
+            for col in 0..size {
+                if col > 0 && col % box_cols == 0 {
+                    line.push_str(" |");
+                }
+                line.push(' ');
+                line.push(geometry.digit_char(self.values[row * size + col]));
+            }
+            writeln!(f, "{}", &line[1..])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Geometry, Grid};
+    use crate::error::SudokuError;
+    use crate::parser::ParseMode;
+
+    const GRID: &str = "\
+1 . . | . . 6
+. . 6 | 1 . .
+------+------
+. 1 . | . 6 .
+. 6 . | . 1 .
+------+------
+. . 1 | 6 . .
+6 . . | . . 1
+";
+
+    #[test]
+    fn test_solve() {
+        let geometry = Geometry::for_size(6).unwrap();
+        let mut grid = Grid::parse(geometry.clone(), GRID, ParseMode::Lenient).unwrap();
+        assert_eq!(grid.to_string(), GRID);
+        assert_eq!(grid.clues(), 12);
+        assert!(grid.is_valid());
+        assert!(grid.count_solutions(2) > 1);
+        assert_eq!(grid.unique_solution(), Err(SudokuError::MultipleSolutions));
+        grid.solve().unwrap();
+        assert!(grid.is_solved());
+
+        let mut twice = Grid::new(geometry);
+        twice.set_value(0, 6);
+        twice.set_value(5, 6);
+        assert!(!twice.is_valid());
+        assert_eq!(twice.count_solutions(2), 0);
+        assert_eq!(twice.solve(), Err(SudokuError::NoSolution));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let geometry = Geometry::for_size(12).unwrap();
+        let mut grid = Grid::new(geometry.clone());
+        grid.set_value(0, 12);
+        grid.set_value(143, 9);
+        let serialized = grid.serialize();
+        assert_eq!(serialized.len(), 144);
+        assert!(serialized.starts_with("C.") && serialized.ends_with(".9"));
+        assert_eq!(
+            Grid::parse(geometry, &serialized, ParseMode::Strict),
+            Ok(grid)
+        );
+    }
+}
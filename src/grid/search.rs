@@ -0,0 +1,224 @@
+use super::Geometry;
+
+/**
+ * The search state of a grid of any geometry, like `BitBoard` for the classic board: the value
+ * of every cell and, for the empty ones, a mask of their candidates with bit (c - 1) for the
+ * candidate c. Placing a digit removes it from the peers, and the naked and hidden singles
+ * this creates are placed at once.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GridState<'a> {
+    geometry: &'a Geometry,
+    values: Vec<u8>,
+    // 0 for a placed cell
+    candidates: Vec<u32>,
+}
+
+impl<'a> GridState<'a> {
+    // the state of a game with its givens placed, None if they already contradict each other
+    pub fn new(geometry: &'a Geometry, game: &[u8]) -> Option<Self> {
+        let mut state = GridState {
+            geometry,
+            values: vec![0; geometry.cells()],
+            candidates: vec![geometry.all_digits(); geometry.cells()],
+        };
+        for (index, &value) in game.iter().enumerate() {
+            if value > 0 && !state.assign(index, value) {
+                return None;
+            }
+        }
+        Some(state)
+    }
+
+    // place `value` in a cell, false on a contradiction
+    pub fn assign(&mut self, index: usize, value: u8) -> bool {
+        if self.values[index] == value {
+            return true;
+        }
+        let bit = 1 << (value - 1);
+        if self.candidates[index] & bit == 0 {
+            return false;
+        }
+        let others = self.candidates[index] & !bit;
+        self.values[index] = value;
+        self.candidates[index] = 0;
+
+        let geometry = self.geometry;
+        for &peer in geometry.peers(index) {
+            if !self.eliminate(peer, value) {
+                return false;
+            }
+        }
+        // the other candidates of the cell may now have a single place left in its units
+        let mut others = others;
+        while others != 0 {
+            let digit = others.trailing_zeros() as u8 + 1;
+            others &= others - 1;
+            for unit in geometry.cell_units(index) {
+                if !self.check_unit(unit, digit) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    // remove a candidate from a cell, false on a contradiction
+    pub fn eliminate(&mut self, index: usize, value: u8) -> bool {
+        if self.values[index] == value {
+            return false;
+        }
+        let bit = 1 << (value - 1);
+        if self.candidates[index] & bit == 0 {
+            return true;
+        }
+        self.candidates[index] &= !bit;
+        match self.candidates[index].count_ones() {
+            0 => return false,
+            1 => {
+                let single = self.candidates[index].trailing_zeros() as u8 + 1;
+                if !self.assign(index, single) {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+        for unit in self.geometry.cell_units(index) {
+            if !self.check_unit(unit, value) {
+                return false;
+            }
+        }
+        true
+    }
+
+    // place `value` if it has a single place left in the unit, false if it has none
+    fn check_unit(&mut self, unit: usize, value: u8) -> bool {
+        let bit = 1 << (value - 1);
+        let mut place = None;
+        for &index in &self.geometry.units()[unit] {
+            if self.values[index] == value {
+                return true;
+            }
+            if self.candidates[index] & bit != 0 {
+                if place.is_some() {
+                    return true;
+                }
+                place = Some(index);
+            }
+        }
+        match place {
+            Some(index) => self.assign(index, value),
+            None => false,
+        }
+    }
+
+    // the empty cell with the fewest candidates, None if the grid is full
+    fn next_cell(&self) -> Option<usize> {
+        let mut best = None;
+        let mut min = u32::MAX;
+        for (index, candidates) in self.candidates.iter().enumerate() {
+            let count = candidates.count_ones();
+            if count > 0 && count < min {
+                min = count;
+                best = Some(index);
+                if count <= 2 {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    /**
+     * Call `found` with every solution until `limit` of them are found, and return how many
+     * were found. The candidates of each cell are tried in the order `order` leaves them in.
+     * Each attempt consumes one unit of `budget`, and the search stops once it is used up, so
+     * a search that leaves no budget may have missed solutions.
+     */
+    pub fn search(
+        &self,
+        limit: usize,
+        budget: &mut usize,
+        order: &mut dyn FnMut(&mut [u8]),
+        found: &mut dyn FnMut(&[u8]),
+    ) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+        let index = match self.next_cell() {
+            Some(index) => index,
+            None => {
+                found(&self.values);
+                return 1;
+            }
+        };
+        let mut digits: Vec<u8> = (1..=self.geometry.size() as u8)
+            .filter(|value| self.candidates[index] & (1 << (value - 1)) != 0)
+            .collect();
+        order(&mut digits);
+
+        let mut count = 0;
+        for value in digits {
+            if *budget == 0 {
+                break;
+            }
+            *budget -= 1;
+            let mut next = self.clone();
+            if next.assign(index, value) {
+                count += next.search(limit - count, budget, order, found);
+                if count >= limit {
+                    break;
+                }
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GridState;
+    use crate::grid::Geometry;
+
+    #[test]
+    fn test_singles() {
+        let geometry = Geometry::new(2, 2).unwrap();
+        // the last cell of the first row is a naked single
+        let state =
+            GridState::new(&geometry, &[1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(state.values[3], 4);
+        assert_eq!(state.candidates[3], 0);
+        // 3 and 4 are left for the second row of the first box
+        assert_eq!(state.candidates[4], 0b1100);
+
+        // two 1s in a row
+        assert!(
+            GridState::new(&geometry, &[1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).is_none()
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let geometry = Geometry::new(2, 2).unwrap();
+        let state = GridState::new(&geometry, &[0; 16]).unwrap();
+        let mut budget = usize::MAX;
+        // there are 288 4x4 solution grids
+        assert_eq!(
+            state.search(1000, &mut budget, &mut |_| {}, &mut |_| {}),
+            288
+        );
+
+        let mut budget = 3;
+        let count = state.search(1000, &mut budget, &mut |_| {}, &mut |_| {});
+        assert_eq!(budget, 0);
+        assert!(count < 288);
+
+        let mut solution = Vec::new();
+        let mut budget = usize::MAX;
+        let reverse = &mut |digits: &mut [u8]| digits.reverse();
+        state.search(1, &mut budget, reverse, &mut |values| {
+            solution = values.to_vec()
+        });
+        assert_eq!(solution[..4], [4, 3, 2, 1]);
+    }
+}
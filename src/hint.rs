@@ -0,0 +1,189 @@
+use crate::board::{cell_name, Board};
+use crate::cell::one_hot;
+use crate::error::SudokuError;
+use crate::logic::{LogicSolver, SolveStep};
+use crate::solver::unique_solution;
+
+/**
+ * How much of a hint is revealed, from a nudge towards the region to look at, to the technique
+ * to use there, to the full deduction with its placements and eliminations.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reveal {
+    Region,
+    Technique,
+    Placement,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hint {
+    // cells whose value or pencil marks contradict the solution
+    Mistakes(Vec<u8>),
+    // the simplest deduction available
+    Step(SolveStep),
+}
+
+impl Hint {
+    pub fn reveal(&self, level: Reveal) -> String {
+        match self {
+            Hint::Mistakes(cells) => {
+                let names: Vec<String> = cells.iter().map(|c| cell_name(*c)).collect();
+                format!("Wrong value or pencil marks in {}", names.join(", "))
+            }
+            Hint::Step(step) => match level {
+                Reveal::Region => format!("Look at {}", region(step)),
+                Reveal::Technique => format!("{} in {}", step.technique, region(step)),
+                Reveal::Placement => step.description(),
+            },
+        }
+    }
+}
+
+// the units the step is based on, or its cells when it has no unit
+fn region(step: &SolveStep) -> String {
+    if !step.units.is_empty() {
+        let units: Vec<String> = step.units.iter().map(|u| u.to_string()).collect();
+        units.join(", ")
+    } else {
+        let cells: Vec<String> = step.cells.iter().map(|c| cell_name(*c)).collect();
+        cells.join(", ")
+    }
+}
+
+/**
+ * The next move for a grid being solved. The readonly cells are the givens, or every fixed cell
+ * when none is readonly, and they must have a unique solution. The cells filled by the user that
+ * differ from the solution, and the pencil marks that lost the solution digit, are reported
+ * first. Otherwise the simplest step on the user's pencil marks is returned, an empty cell
+ * without marks counting as having every digit its peers allow.
+ * Returns None when the grid is solved or no technique applies, and an error when the givens do
+ * not have a unique solution.
+ */
+pub fn hint(board: &Board) -> Result<Option<Hint>, SudokuError> {
+    let givens = board.givens();
+
+    let solution = unique_solution(&givens)?;
+
+    let mut mistakes = Vec::new();
+    for (i, cell) in board.cells.iter().enumerate() {
+        let expected = solution[i];
+        let wrong = if cell.is_fixed() {
+            cell.get_value() != expected
+        } else {
+            cell.get_candidates() != 0 && cell.get_candidates() & one_hot(expected) == 0
+        };
+        if wrong {
+            mistakes.push(i as u8);
+        }
+    }
+    if !mistakes.is_empty() {
+        return Ok(Some(Hint::Mistakes(mistakes)));
+    }
+
+    let values: Vec<u8> = board.cells.iter().map(|cell| cell.get_value()).collect();
+    let mut grid = Board::new();
+    grid.load_candidates(&values);
+    for (i, cell) in board.cells.iter().enumerate() {
+        let marks = cell.get_candidates();
+        if !cell.is_fixed() && marks != 0 {
+            let allowed = grid.cells[i].get_candidates() & marks;
+            grid.cells[i].restore((0, allowed));
+        }
+    }
+
+    Ok(LogicSolver::new().next_step(&grid).map(Hint::Step))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hint, Hint, Reveal};
+    use crate::board::{game_str_to_vec, Board};
+    use crate::error::SudokuError;
+    use crate::logic::{LogicSolver, TechniqueKind};
+
+    fn load(game: &str) -> Board {
+        let mut board = Board::new();
+        board.load_game(&game_str_to_vec(game).unwrap()).unwrap();
+        for cell in &mut board.cells {
+            if cell.is_fixed() {
+                cell.set_readonly(true);
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn test_hint_step() {
+        let board = load(
+            "62851943757942361841378652985793214636214789519486527378135496223569178494627835.",
+        );
+        let h = hint(&board).unwrap().unwrap();
+        assert_eq!(h.reveal(Reveal::Region), "Look at row 9");
+        assert_eq!(h.reveal(Reveal::Technique), "Hidden Single in row 9");
+        assert_eq!(
+            h.reveal(Reveal::Placement),
+            "Hidden single 1 in row 9 sets r9c9 to 1"
+        );
+    }
+
+    #[test]
+    fn test_hint_pencil_marks() {
+        let mut board = load(
+            "6....5.......217...58.....3....4..9......7.1..9.582.7.3.7..85...86..........7....",
+        );
+        // play the simple steps, keeping the values and pencil marks they leave
+        let values: Vec<u8> = board.cells.iter().map(|cell| cell.get_value()).collect();
+        let mut grid = Board::new();
+        grid.load_candidates(&values);
+        let solver = LogicSolver::new();
+        let x_wing = loop {
+            let step = solver.next_step(&grid).unwrap();
+            if step.technique == TechniqueKind::XWing {
+                break step;
+            }
+            step.apply(&mut grid);
+        };
+        for i in 0..81 {
+            if !board.cells[i].is_readonly() {
+                board.cells[i].restore(grid.cells[i].backup());
+            }
+        }
+        assert_eq!(hint(&board), Ok(Some(Hint::Step(x_wing.clone()))));
+
+        // once its eliminations are marked, the X-Wing is no longer the hint
+        x_wing.apply(&mut board);
+        match hint(&board) {
+            Ok(Some(Hint::Step(step))) => assert_ne!(step, x_wing),
+            h => panic!("unexpected hint {:?}", h),
+        }
+    }
+
+    #[test]
+    fn test_hint_mistakes() {
+        let mut board = load(
+            "62851943757942361841378652985793214636214789519486527378135496223569178494627835.",
+        );
+        board.cells[80].add_candidate(2);
+        assert_eq!(hint(&board), Ok(Some(Hint::Mistakes(vec![80]))));
+
+        board.cells[80].set_value(2);
+        let h = hint(&board).unwrap().unwrap();
+        assert_eq!(h, Hint::Mistakes(vec![80]));
+        assert_eq!(
+            h.reveal(Reveal::Region),
+            "Wrong value or pencil marks in r9c9"
+        );
+
+        // solved grid
+        board.cells[80].restore((1, 0));
+        assert_eq!(hint(&board), Ok(None));
+    }
+
+    #[test]
+    fn test_hint_no_unique_solution() {
+        let board = load(
+            "6285194375794236184137865298.793214.3.214789.194865273781354962235691784946278351",
+        );
+        assert_eq!(hint(&board), Err(SudokuError::MultipleSolutions));
+    }
+}
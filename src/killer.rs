@@ -0,0 +1,244 @@
+//! Killer Sudoku: the grid is split into cages, each holding different digits that add up to
+//! the sum of the cage, and usually no given at all. A `Cage` is a `Constraint`, so a killer
+//! puzzle is played with `Board::add_constraint` and generated with `Generator::generate_killer`.
+
+use crate::bitboard::BitBoard;
+use crate::board::cell_name;
+use crate::constraint::{check_all_different, check_region, propagate_all_different, Constraint};
+use crate::error::SudokuError;
+use crate::logic::digits;
+use std::sync::Arc;
+
+/**
+ * Cells holding different digits with a given sum. The digits the cage can hold come from the
+ * table of the sets of as many different digits as it has cells that add up to its sum.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cage {
+    cells: Vec<u8>,
+    sum: u8,
+    // the digit sets the cage can hold, as candidate masks
+    combinations: Vec<u16>,
+}
+
+impl Cage {
+    // the cells are kept in reading order, the first one is the top left cell of the cage
+    pub fn new(mut cells: Vec<u8>, sum: u8) -> Result<Self, SudokuError> {
+        check_region(&cells)?;
+        let combinations = combinations(cells.len(), sum);
+        if combinations.is_empty() {
+            return Err(SudokuError::InvalidSum {
+                sum,
+                size: cells.len(),
+            });
+        }
+        cells.sort_unstable();
+        Ok(Cage {
+            cells,
+            sum,
+            combinations,
+        })
+    }
+
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    pub fn sum(&self) -> u8 {
+        self.sum
+    }
+}
+
+/**
+ * The sets of `size` different digits adding up to `sum`, as candidate masks: 17 in two cells
+ * is only 8 and 9, 10 in three cells is 1 2 7, 1 3 6, 1 4 5 or 2 3 5.
+ */
+pub fn combinations(size: usize, sum: u8) -> Vec<u16> {
+    (0..1u16 << 9)
+        .filter(|&mask| {
+            mask.count_ones() as usize == size
+                && digits(mask).iter().map(|&d| d as u32).sum::<u32>() == sum as u32
+        })
+        .collect()
+}
+
+// one rule per cage
+pub fn constraints(cages: &[Cage]) -> Vec<Arc<dyn Constraint>> {
+    cages
+        .iter()
+        .map(|cage| Arc::new(cage.clone()) as Arc<dyn Constraint>)
+        .collect()
+}
+
+impl Constraint for Cage {
+    fn describe(&self) -> String {
+        let cells: Vec<String> = self.cells.iter().map(|&cell| cell_name(cell)).collect();
+        format!("cage {} {}", self.sum, cells.join(" "))
+    }
+
+    // the placed digits must be different and belong to one of the combinations
+    fn check(&self, game: &[u8]) -> bool {
+        if !check_all_different(&self.cells, game) {
+            return false;
+        }
+        let placed = self
+            .cells
+            .iter()
+            .fold(0u16, |mask, &cell| match game[cell as usize] {
+                0 => mask,
+                value => mask | 1 << (value - 1),
+            });
+        self.combinations
+            .iter()
+            .any(|&combination| combination & placed == placed)
+    }
+
+    fn propagate(&self, board: &mut BitBoard) -> bool {
+        if !propagate_all_different(&self.cells, board) {
+            return false;
+        }
+        let (mut placed, mut open) = (0u16, 0u16);
+        for &cell in &self.cells {
+            match board.value(cell) {
+                0 => open |= board.cell_candidates(cell),
+                value => placed |= 1 << (value - 1),
+            }
+        }
+
+        // the combinations still possible: every placed digit and the rest within the candidates
+        let (mut possible, mut allowed, mut required) = (false, 0u16, 0x1ffu16);
+        for &combination in &self.combinations {
+            let rest = combination & !placed;
+            if combination & placed == placed && rest & !open == 0 {
+                possible = true;
+                allowed |= rest;
+                required &= rest;
+            }
+        }
+        if !possible {
+            return false;
+        }
+
+        // the digits of no possible combination leave the cage
+        for &cell in &self.cells {
+            if board.value(cell) > 0 {
+                continue;
+            }
+            for value in digits(board.cell_candidates(cell) & !allowed) {
+                if !board.eliminate(cell, value) {
+                    return false;
+                }
+            }
+        }
+        // a digit of every possible combination with a single place goes there
+        for value in digits(required) {
+            if self.cells.iter().any(|&cell| board.value(cell) == value) {
+                continue;
+            }
+            let mut places = self.cells.iter().filter(|&&cell| {
+                board.value(cell) == 0 && board.cell_candidates(cell) & (1 << (value - 1)) != 0
+            });
+            match (places.next(), places.next()) {
+                (None, _) => return false,
+                (Some(&cell), None) if !board.assign(cell, value) => return false,
+                _ => {}
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{combinations, constraints, Cage};
+    use crate::bitboard::BitBoard;
+    use crate::constraint::{propagate, search, Constraint};
+    use crate::error::SudokuError;
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(combinations(2, 17), vec![0b1_1000_0000]);
+        assert_eq!(combinations(3, 10).len(), 4);
+        assert_eq!(combinations(9, 45), vec![0x1ff]);
+        assert!(combinations(2, 2).is_empty());
+        assert!(combinations(1, 10).is_empty());
+    }
+
+    #[test]
+    fn test_cage() {
+        let cage = Cage::new(vec![1, 0], 17).unwrap();
+        assert_eq!(cage.cells(), &[0, 1]);
+        assert_eq!(cage.sum(), 17);
+        assert_eq!(cage.describe(), "cage 17 r1c1 r1c2");
+
+        let mut game = vec![0; 81];
+        assert!(cage.check(&game));
+        game[0] = 9;
+        assert!(cage.check(&game));
+        game[1] = 9;
+        assert!(!cage.check(&game));
+        game[1] = 7;
+        assert!(!cage.check(&game));
+        game[0] = 8;
+        game[1] = 0;
+        assert!(!Cage::new(vec![0, 1], 3).unwrap().check(&game));
+
+        // only 8 and 9 make 17, and 9 is ruled out of r1c2
+        let mut board = BitBoard::new();
+        assert!(board.eliminate(1, 9));
+        assert!(cage.propagate(&mut board));
+        assert_eq!(board.value(0), 9);
+        assert_eq!(board.value(1), 8);
+
+        assert_eq!(
+            Cage::new(vec![0, 1, 2], 30),
+            Err(SudokuError::InvalidSum { sum: 30, size: 3 })
+        );
+        assert_eq!(
+            Cage::new(vec![0, 1], 2),
+            Err(SudokuError::InvalidSum { sum: 2, size: 2 })
+        );
+        assert_eq!(
+            Cage::new(vec![], 0),
+            Err(SudokuError::InvalidSize { size: 0 })
+        );
+        assert_eq!(
+            Cage::new(vec![80, 90], 3),
+            Err(SudokuError::InvalidCell { cell: 90 })
+        );
+        assert_eq!(
+            Cage::new(vec![0, 0], 3),
+            Err(SudokuError::RepeatedCell { cell: 0 })
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        // 24 in three cells is only 7 8 9, and 6 is only 1 2 3
+        let cages = constraints(&[
+            Cage::new(vec![0, 1, 2], 24).unwrap(),
+            Cage::new(vec![3, 4, 5], 15).unwrap(),
+            Cage::new(vec![6, 7, 8], 6).unwrap(),
+        ]);
+        let mut board = BitBoard::new();
+        assert!(propagate(&mut board, &cages));
+        assert_eq!(board.cell_candidates(0), 0b1_1100_0000);
+        assert_eq!(board.cell_candidates(8), 0b111);
+
+        let mut count = 0;
+        search(&board, &cages, 3, &mut |solution| {
+            let game = solution.to_game();
+            assert!(cages.iter().all(|cage| cage.check(&game)));
+            assert_eq!(game[..3].iter().sum::<u8>(), 24);
+            count += 1;
+        });
+        assert_eq!(count, 3);
+
+        // r1c1 cannot be both 1 and one of 8 and 9
+        let impossible = constraints(&[
+            Cage::new(vec![0, 1], 17).unwrap(),
+            Cage::new(vec![0], 1).unwrap(),
+        ]);
+        assert!(!propagate(&mut BitBoard::new(), &impossible));
+    }
+}
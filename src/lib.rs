@@ -1,6 +1,25 @@
-mod board;
-mod cell;
+pub mod asynchronous;
+pub mod batch;
+pub mod bitboard;
+pub mod board;
+pub mod cell;
+pub mod collection;
+pub mod constraint;
+pub mod difficulty;
+pub mod error;
+pub mod formats;
 pub mod generator;
+pub mod grid;
+pub mod hint;
+pub mod killer;
+pub mod logic;
+pub mod parser;
+pub mod pencilmarks;
+pub mod puzzle;
+pub mod rating;
+pub mod solver;
+pub mod symmetry;
+pub mod variant;
 
 #[cfg(test)]
 mod tests {
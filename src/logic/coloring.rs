@@ -0,0 +1,152 @@
+use super::{cells_with, has_candidate, sees, SolveStep, Technique, TechniqueKind};
+use crate::board::{Board, UnitId};
+
+/**
+ * Cells linked by units where a digit fits in exactly two cells are given alternating colours:
+ * either all the cells of one colour hold the digit, or all the cells of the other.
+ * If two cells of the same colour see each other, that colour is false (colour wrap).
+ * A cell seeing both colours can not hold the digit (colour trap).
+ */
+pub struct SimpleColouring;
+
+impl Technique for SimpleColouring {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        for value in 1..=9 {
+            let mut links: Vec<(u8, u8)> = Vec::new();
+            for unit in UnitId::all() {
+                let cells = cells_with(board, unit.cells(), value);
+                if cells.len() == 2 {
+                    links.push((cells[0], cells[1]));
+                }
+            }
+
+            let mut colours: [Option<(usize, bool)>; 81] = [None; 81];
+            let mut chain = 0;
+            for (start, _) in &links {
+                if colours[*start as usize].is_some() {
+                    continue;
+                }
+                colours[*start as usize] = Some((chain, true));
+                let mut stack = vec![*start];
+                while let Some(index) = stack.pop() {
+                    let colour = colours[index as usize].unwrap().1;
+                    for (a, b) in &links {
+                        let next = match (*a == index, *b == index) {
+                            (true, _) => *b,
+                            (_, true) => *a,
+                            _ => continue,
+                        };
+                        if colours[next as usize].is_none() {
+                            colours[next as usize] = Some((chain, !colour));
+                            stack.push(next);
+                        }
+                    }
+                }
+
+                if let Some(step) = check_chain(board, &colours, chain, value) {
+                    return Some(step);
+                }
+                chain += 1;
+            }
+        }
+        None
+    }
+}
+
+fn check_chain(
+    board: &Board,
+    colours: &[Option<(usize, bool)>; 81],
+    chain: usize,
+    value: u8,
+) -> Option<SolveStep> {
+    let coloured = |colour: bool| -> Vec<u8> {
+        (0..81)
+            .filter(|index| colours[*index as usize] == Some((chain, colour)))
+            .collect()
+    };
+    let on = coloured(true);
+    let off = coloured(false);
+    let chain_cells: Vec<u8> = on.iter().chain(off.iter()).copied().collect();
+
+    // colour wrap
+    for cells in [&on, &off] {
+        let wrapped = cells.iter().any(|a| cells.iter().any(|b| sees(*a, *b)));
+        if wrapped {
+            return Some(SolveStep {
+                technique: TechniqueKind::SimpleColouring,
+                cells: chain_cells,
+                units: vec![],
+                digits: vec![value],
+                placements: vec![],
+                eliminations: cells.iter().map(|index| (*index, value)).collect(),
+            });
+        }
+    }
+
+    // colour trap
+    let eliminations: Vec<(u8, u8)> = (0..81)
+        .filter(|index| colours[*index as usize].is_none() && has_candidate(board, *index, value))
+        .filter(|index| on.iter().any(|cell| sees(*index, *cell)))
+        .filter(|index| off.iter().any(|cell| sees(*index, *cell)))
+        .map(|index| (index, value))
+        .collect();
+    if !eliminations.is_empty() {
+        return Some(SolveStep {
+            technique: TechniqueKind::SimpleColouring,
+            cells: chain_cells,
+            units: vec![],
+            digits: vec![value],
+            placements: vec![],
+            eliminations,
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SimpleColouring;
+    use crate::board::Board;
+    use crate::logic::Technique;
+
+    #[test]
+    fn test_colour_trap() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(SimpleColouring.find(&b), None);
+        // 1 of row 1 and row 5 only fits in columns 1 and 5, and so does 1 of column 5
+        for col in [1, 2, 3, 5, 6, 7, 8] {
+            b.cells[col].remove_candidate(1);
+            b.cells[36 + col].remove_candidate(1);
+        }
+        for row in [1, 2, 3, 5, 6, 7, 8] {
+            b.cells[row * 9 + 4].remove_candidate(1);
+        }
+        let d = SimpleColouring.find(&b).unwrap();
+        // the rest of column 1 sees both colours
+        assert_eq!(
+            d.eliminations,
+            vec![(9, 1), (18, 1), (27, 1), (45, 1), (54, 1), (63, 1), (72, 1)]
+        );
+    }
+
+    #[test]
+    fn test_colour_wrap() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        // 2 makes the chain r1c1 - r1c5 - r4c5 - r4c3 - r2c3, whose ends see each other
+        let keep = |unit: [usize; 9], kept: [usize; 2], b: &mut Board| {
+            for index in unit {
+                if !kept.contains(&index) {
+                    b.cells[index].remove_candidate(2);
+                }
+            }
+        };
+        keep([0, 1, 2, 3, 4, 5, 6, 7, 8], [0, 4], &mut b);
+        keep([4, 13, 22, 31, 40, 49, 58, 67, 76], [4, 31], &mut b);
+        keep([27, 28, 29, 30, 31, 32, 33, 34, 35], [29, 31], &mut b);
+        keep([2, 11, 20, 29, 38, 47, 56, 65, 74], [11, 29], &mut b);
+        let d = SimpleColouring.find(&b).unwrap();
+        assert_eq!(d.eliminations, vec![(0, 2), (11, 2), (31, 2)]);
+    }
+}
@@ -0,0 +1,134 @@
+use super::{cells_with, col_of, combinations, row_of, SolveStep, Technique, TechniqueKind};
+use crate::board::{Board, UnitId};
+
+/**
+ * N rows where a digit fits only in the same N columns: each column gets the digit from one of
+ * these rows, so it can be removed from the other cells of the columns. The same holds with
+ * rows and columns swapped. N is 2 for an X-Wing, 3 for a Swordfish and 4 for a Jellyfish.
+ */
+pub struct Fish {
+    size: usize,
+}
+
+impl Fish {
+    pub fn new(size: usize) -> Self {
+        Fish { size }
+    }
+
+    fn kind(&self) -> TechniqueKind {
+        match self.size {
+            2 => TechniqueKind::XWing,
+            3 => TechniqueKind::Swordfish,
+            _ => TechniqueKind::Jellyfish,
+        }
+    }
+
+    fn find_in(
+        &self,
+        board: &Board,
+        base: fn(u8) -> UnitId,
+        cover: fn(u8) -> UnitId,
+        cover_of: fn(u8) -> u8,
+    ) -> Option<SolveStep> {
+        for value in 1..=9 {
+            let lines: Vec<u8> = (0..9)
+                .filter(|line| {
+                    let count = cells_with(board, base(*line).cells(), value).len();
+                    count >= 2 && count <= self.size
+                })
+                .collect();
+
+            for subset in combinations(&lines, self.size) {
+                let cells: Vec<u8> = subset
+                    .iter()
+                    .flat_map(|line| cells_with(board, base(*line).cells(), value))
+                    .collect();
+                let mut cover_lines: Vec<u8> = cells.iter().map(|index| cover_of(*index)).collect();
+                cover_lines.sort_unstable();
+                cover_lines.dedup();
+                if cover_lines.len() != self.size {
+                    continue;
+                }
+
+                let mut eliminations = Vec::new();
+                for line in &cover_lines {
+                    for index in cells_with(board, cover(*line).cells(), value) {
+                        if !cells.contains(&index) {
+                            eliminations.push((index, value));
+                        }
+                    }
+                }
+                if !eliminations.is_empty() {
+                    eliminations.sort_unstable();
+                    let units = subset
+                        .iter()
+                        .map(|line| base(*line))
+                        .chain(cover_lines.iter().map(|line| cover(*line)))
+                        .collect();
+                    return Some(SolveStep {
+                        technique: self.kind(),
+                        cells,
+                        units,
+                        digits: vec![value],
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Technique for Fish {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        self.find_in(board, UnitId::Row, UnitId::Column, col_of)
+            .or_else(|| self.find_in(board, UnitId::Column, UnitId::Row, row_of))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fish;
+    use crate::board::Board;
+    use crate::logic::{Technique, TechniqueKind};
+
+    #[test]
+    fn test_x_wing() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(Fish::new(2).find(&b), None);
+        // 7 of rows 1 and 5 only fits in columns 3 and 7
+        for col in [0, 1, 3, 4, 5, 7, 8] {
+            b.cells[col].remove_candidate(7);
+            b.cells[36 + col].remove_candidate(7);
+        }
+        let d = Fish::new(2).find(&b).unwrap();
+        assert_eq!(d.technique, TechniqueKind::XWing);
+        assert_eq!(d.eliminations.len(), 14);
+        assert!(d.eliminations.contains(&(11, 7)));
+        assert!(d.eliminations.contains(&(78, 7)));
+        assert!(!d.eliminations.contains(&(2, 7)));
+    }
+
+    #[test]
+    fn test_swordfish() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        // 7 of columns 1, 4 and 7 only fits in rows 2, 5 and 8, two cells each
+        let rows = [[1, 4], [4, 7], [1, 7]];
+        for (i, col) in [0, 3, 6].iter().enumerate() {
+            for row in 0..9 {
+                if !rows[i].contains(&row) {
+                    b.cells[row * 9 + col].remove_candidate(7);
+                }
+            }
+        }
+        assert_eq!(Fish::new(2).find(&b), None);
+        let d = Fish::new(3).find(&b).unwrap();
+        assert_eq!(d.technique, TechniqueKind::Swordfish);
+        assert_eq!(d.eliminations.len(), 18);
+        assert!(d.eliminations.contains(&(10, 7)));
+        assert!(!d.eliminations.contains(&(9, 7)));
+    }
+}
@@ -0,0 +1,129 @@
+use super::{block_of, cells_with, col_of, row_of, SolveStep, Technique, TechniqueKind};
+use crate::board::{Board, UnitId};
+
+/**
+ * The candidates of a digit in a block are all in one row (or column): the digit must be in
+ * this part of the line, so it can be removed from the rest of the line.
+ */
+pub struct PointingPair;
+
+impl Technique for PointingPair {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        for block in (0..9).map(UnitId::Block) {
+            for value in 1..=9 {
+                let cells = cells_with(board, block.cells(), value);
+                if cells.len() < 2 {
+                    continue;
+                }
+                let lines = [
+                    (UnitId::Row(row_of(cells[0])), row_of as fn(u8) -> u8),
+                    (UnitId::Column(col_of(cells[0])), col_of),
+                ];
+                for (line, line_of) in lines {
+                    if !cells
+                        .iter()
+                        .all(|index| line_of(*index) == line_of(cells[0]))
+                    {
+                        continue;
+                    }
+                    let eliminations: Vec<(u8, u8)> = cells_with(board, line.cells(), value)
+                        .into_iter()
+                        .filter(|index| !block.cells().contains(index))
+                        .map(|index| (index, value))
+                        .collect();
+                    if !eliminations.is_empty() {
+                        return Some(SolveStep {
+                            technique: TechniqueKind::PointingPair,
+                            cells,
+                            units: vec![block, line],
+                            digits: vec![value],
+                            placements: vec![],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/**
+ * The candidates of a digit in a row (or column) are all in one block: the digit must be in
+ * this part of the block, so it can be removed from the rest of the block.
+ */
+pub struct BoxLineReduction;
+
+impl Technique for BoxLineReduction {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        let lines = (0..9).map(UnitId::Row).chain((0..9).map(UnitId::Column));
+        for line in lines {
+            for value in 1..=9 {
+                let cells = cells_with(board, line.cells(), value);
+                if cells.len() < 2 {
+                    continue;
+                }
+                let block = block_of(cells[0]);
+                if !cells.iter().all(|index| block_of(*index) == block) {
+                    continue;
+                }
+                let block = UnitId::Block(block);
+                let eliminations: Vec<(u8, u8)> = cells_with(board, block.cells(), value)
+                    .into_iter()
+                    .filter(|index| !line.cells().contains(index))
+                    .map(|index| (index, value))
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some(SolveStep {
+                        technique: TechniqueKind::BoxLineReduction,
+                        cells,
+                        units: vec![line, block],
+                        digits: vec![value],
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoxLineReduction, PointingPair};
+    use crate::board::Board;
+    use crate::logic::Technique;
+
+    #[test]
+    fn test_pointing_pair() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(PointingPair.find(&b), None);
+        // 5 of block 1 is in the first row
+        for index in [9, 10, 11, 18, 19, 20] {
+            b.cells[index].remove_candidate(5);
+        }
+        let d = PointingPair.find(&b).unwrap();
+        assert_eq!(
+            d.eliminations,
+            vec![(3, 5), (4, 5), (5, 5), (6, 5), (7, 5), (8, 5)]
+        );
+    }
+
+    #[test]
+    fn test_box_line_reduction() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(BoxLineReduction.find(&b), None);
+        // 5 of row 1 is in block 1
+        for index in 3..9 {
+            b.cells[index].remove_candidate(5);
+        }
+        let d = BoxLineReduction.find(&b).unwrap();
+        assert_eq!(
+            d.eliminations,
+            vec![(9, 5), (10, 5), (11, 5), (18, 5), (19, 5), (20, 5)]
+        );
+    }
+}
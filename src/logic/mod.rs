@@ -0,0 +1,478 @@
+//! Human style solving: every technique looks for one deduction on the candidates of a board,
+//! and the solver applies the simplest deduction available until the board is solved or no
+//! technique applies any more. No guess is ever made.
+
+use crate::board::{cell_name, Board, UnitId};
+use std::collections::BTreeMap;
+use std::fmt;
+
+mod coloring;
+mod fish;
+mod intersections;
+mod singles;
+mod subsets;
+mod uniqueness;
+mod wings;
+
+pub use coloring::SimpleColouring;
+pub use fish::Fish;
+pub use intersections::{BoxLineReduction, PointingPair};
+pub use singles::{HiddenSingle, NakedSingle};
+pub use subsets::{HiddenSubset, NakedSubset};
+pub use uniqueness::{BugPlusOne, UniqueRectangle};
+pub use wings::{WWing, XYWing, XYZWing};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TechniqueKind {
+    HiddenSingle,
+    NakedSingle,
+    PointingPair,
+    BoxLineReduction,
+    NakedPair,
+    XWing,
+    HiddenPair,
+    NakedTriple,
+    Swordfish,
+    HiddenTriple,
+    XYWing,
+    XYZWing,
+    WWing,
+    UniqueRectangle,
+    NakedQuad,
+    Jellyfish,
+    HiddenQuad,
+    BugPlusOne,
+    SimpleColouring,
+    // not a technique of the solver: the guess `rating::rate` makes when the techniques get stuck
+    Guess,
+}
+
+impl TechniqueKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TechniqueKind::HiddenSingle => "Hidden Single",
+            TechniqueKind::NakedSingle => "Naked Single",
+            TechniqueKind::PointingPair => "Pointing Pair",
+            TechniqueKind::BoxLineReduction => "Box/Line Reduction",
+            TechniqueKind::NakedPair => "Naked Pair",
+            TechniqueKind::XWing => "X-Wing",
+            TechniqueKind::HiddenPair => "Hidden Pair",
+            TechniqueKind::NakedTriple => "Naked Triple",
+            TechniqueKind::Swordfish => "Swordfish",
+            TechniqueKind::HiddenTriple => "Hidden Triple",
+            TechniqueKind::XYWing => "XY-Wing",
+            TechniqueKind::XYZWing => "XYZ-Wing",
+            TechniqueKind::WWing => "W-Wing",
+            TechniqueKind::UniqueRectangle => "Unique Rectangle",
+            TechniqueKind::NakedQuad => "Naked Quad",
+            TechniqueKind::Jellyfish => "Jellyfish",
+            TechniqueKind::HiddenQuad => "Hidden Quad",
+            TechniqueKind::BugPlusOne => "BUG+1",
+            TechniqueKind::SimpleColouring => "Simple Colouring",
+            TechniqueKind::Guess => "Guess",
+        }
+    }
+}
+
+impl fmt::Display for TechniqueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/**
+ * One deduction found by a technique: the pattern it is based on, given by its cells, units and
+ * digits, and its result, digits to place and candidates to remove, both as (cell, digit).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveStep {
+    pub technique: TechniqueKind,
+    pub cells: Vec<u8>,
+    pub units: Vec<UnitId>,
+    pub digits: Vec<u8>,
+    pub placements: Vec<(u8, u8)>,
+    pub eliminations: Vec<(u8, u8)>,
+}
+
+impl SolveStep {
+    pub fn apply(&self, board: &mut Board) {
+        for (index, value) in &self.placements {
+            board.place(*index, *value);
+        }
+        for (index, value) in &self.eliminations {
+            board.cells[*index as usize].remove_candidate(*value);
+        }
+    }
+
+    /**
+     * A sentence explaining the step, such as
+     * "Naked pair {3,7} in row 4 removes 3 from r4c8, r4c9 and 7 from r4c9".
+     */
+    pub fn description(&self) -> String {
+        let mut name = self.technique.name().to_string();
+        if let Some(space) = name.find(' ') {
+            let rest = name.split_off(space).to_lowercase();
+            name.push_str(&rest);
+        }
+
+        let digits = match self.digits.len() {
+            1 => format!(" {}", self.digits[0]),
+            0 => String::new(),
+            _ => {
+                let list: Vec<String> = self.digits.iter().map(|d| d.to_string()).collect();
+                format!(" {{{}}}", list.join(","))
+            }
+        };
+
+        let location = if !self.units.is_empty() {
+            let units: Vec<String> = self.units.iter().map(|u| u.to_string()).collect();
+            format!(" in {}", join_list(&units))
+        } else if !self.cells.is_empty() {
+            let cells: Vec<String> = self.cells.iter().map(|c| cell_name(*c)).collect();
+            format!(" at {}", join_list(&cells))
+        } else {
+            String::new()
+        };
+
+        let mut effects = Vec::new();
+        for (index, value) in &self.placements {
+            effects.push(format!("sets {} to {}", cell_name(*index), value));
+        }
+        let mut removed: Vec<(u8, Vec<String>)> = Vec::new();
+        for (index, value) in &self.eliminations {
+            match removed.iter_mut().find(|(v, _)| v == value) {
+                Some((_, cells)) => cells.push(cell_name(*index)),
+                None => removed.push((*value, vec![cell_name(*index)])),
+            }
+        }
+        let removals: Vec<String> = removed
+            .iter()
+            .map(|(value, cells)| format!("{} from {}", value, cells.join(", ")))
+            .collect();
+        if !removals.is_empty() {
+            effects.push(format!("removes {}", join_list(&removals)));
+        }
+
+        format!("{}{}{} {}", name, digits, location, join_list(&effects))
+    }
+}
+
+impl fmt::Display for SolveStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+// "a", "a and b", "a, b and c"
+fn join_list(items: &[String]) -> String {
+    match items.len() {
+        0 => String::new(),
+        1 => items[0].clone(),
+        n => format!("{} and {}", items[..n - 1].join(", "), items[n - 1]),
+    }
+}
+
+pub trait Technique {
+    /**
+     * Look for one deduction on the candidates of the board, without changing it.
+     * The board is expected to have been loaded with `Board::load_candidates`.
+     */
+    fn find(&self, board: &Board) -> Option<SolveStep>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LogicResult {
+    pub solved: bool,
+    // how many times each technique was applied
+    pub techniques: BTreeMap<TechniqueKind, usize>,
+}
+
+pub struct LogicSolver {
+    techniques: Vec<Box<dyn Technique>>,
+}
+
+impl Default for LogicSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogicSolver {
+    // a solver with the whole catalogue, simplest techniques first
+    pub fn new() -> Self {
+        LogicSolver::with_techniques(vec![
+            Box::new(HiddenSingle),
+            Box::new(NakedSingle),
+            Box::new(PointingPair),
+            Box::new(BoxLineReduction),
+            Box::new(NakedSubset::new(2)),
+            Box::new(Fish::new(2)),
+            Box::new(HiddenSubset::new(2)),
+            Box::new(NakedSubset::new(3)),
+            Box::new(Fish::new(3)),
+            Box::new(HiddenSubset::new(3)),
+            Box::new(XYWing),
+            Box::new(XYZWing),
+            Box::new(WWing),
+            Box::new(UniqueRectangle),
+            Box::new(NakedSubset::new(4)),
+            Box::new(Fish::new(4)),
+            Box::new(HiddenSubset::new(4)),
+            Box::new(BugPlusOne),
+            Box::new(SimpleColouring),
+        ])
+    }
+
+    // techniques are tried in the given order
+    pub fn with_techniques(techniques: Vec<Box<dyn Technique>>) -> Self {
+        LogicSolver { techniques }
+    }
+
+    pub fn next_step(&self, board: &Board) -> Option<SolveStep> {
+        self.techniques
+            .iter()
+            .find_map(|technique| technique.find(board))
+    }
+
+    /**
+     * Apply steps until the board is solved or stuck, and return them in order.
+     */
+    pub fn solve_steps(&self, board: &mut Board) -> Vec<SolveStep> {
+        let mut steps = Vec::new();
+        while !board.cells.iter().all(|cell| cell.is_fixed()) {
+            match self.next_step(board) {
+                Some(step) => {
+                    step.apply(board);
+                    steps.push(step);
+                }
+                None => break,
+            }
+        }
+        steps
+    }
+
+    pub fn solve(&self, board: &mut Board) -> LogicResult {
+        let mut result = LogicResult::default();
+        for step in self.solve_steps(board) {
+            *result.techniques.entry(step.technique).or_insert(0) += 1;
+        }
+        result.solved = board.is_solved();
+        result
+    }
+}
+
+pub(crate) fn row_of(index: u8) -> u8 {
+    index / 9
+}
+
+pub(crate) fn col_of(index: u8) -> u8 {
+    index % 9
+}
+
+pub(crate) fn block_of(index: u8) -> u8 {
+    (index / 27) * 3 + (index % 9) / 3
+}
+
+// two different cells share a row, a column or a block
+pub(crate) fn sees(a: u8, b: u8) -> bool {
+    a != b && (row_of(a) == row_of(b) || col_of(a) == col_of(b) || block_of(a) == block_of(b))
+}
+
+pub(crate) fn candidates(board: &Board, index: u8) -> u16 {
+    let cell = &board.cells[index as usize];
+    if cell.is_fixed() {
+        0
+    } else {
+        cell.get_candidates()
+    }
+}
+
+pub(crate) fn has_candidate(board: &Board, index: u8, value: u8) -> bool {
+    candidates(board, index) & (1 << (value - 1)) != 0
+}
+
+// the digits of a candidate bit set, in increasing order
+pub(crate) fn digits(mask: u16) -> Vec<u8> {
+    (1..=9).filter(|d| mask & (1 << (d - 1)) != 0).collect()
+}
+
+// the cells of a unit that still have a candidate
+pub(crate) fn cells_with(board: &Board, unit: &[u8], value: u8) -> Vec<u8> {
+    unit.iter()
+        .copied()
+        .filter(|index| has_candidate(board, *index, value))
+        .collect()
+}
+
+// every cell that has the candidate and sees all of the given cells
+pub(crate) fn common_peers_with(board: &Board, cells: &[u8], value: u8) -> Vec<u8> {
+    (0..81)
+        .filter(|index| !cells.contains(index))
+        .filter(|index| cells.iter().all(|cell| sees(*index, *cell)))
+        .filter(|index| has_candidate(board, *index, value))
+        .collect()
+}
+
+// all the combinations of k items, keeping the items order
+pub(crate) fn combinations<T: Copy>(items: &[T], k: usize) -> Vec<Vec<T>> {
+    let mut result = Vec::new();
+    let mut current = Vec::with_capacity(k);
+    collect_combinations(items, k, 0, &mut current, &mut result);
+    result
+}
+
+fn collect_combinations<T: Copy>(
+    items: &[T],
+    k: usize,
+    start: usize,
+    current: &mut Vec<T>,
+    result: &mut Vec<Vec<T>>,
+) {
+    if current.len() == k {
+        result.push(current.clone());
+        return;
+    }
+    for i in start..items.len() {
+        current.push(items[i]);
+        collect_combinations(items, k, i + 1, current, result);
+        current.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{block_of, combinations, sees, LogicSolver, SolveStep, TechniqueKind};
+    use crate::board::{game_str_to_vec, Board, UnitId};
+    use crate::generator::Generator;
+
+    // every placement and elimination made by the solver must agree with the solution
+    fn assert_sound(game: &str) -> bool {
+        let v = game_str_to_vec(game).unwrap();
+        let mut solution = Board::new();
+        solution.init(&v).unwrap();
+        assert_eq!(solution.solve(), Ok(()));
+
+        let solver = LogicSolver::new();
+        let mut board = Board::new();
+        board.load_candidates(&v);
+        while let Some(step) = solver.next_step(&board) {
+            for (index, value) in &step.placements {
+                assert_eq!(solution.cells[*index as usize].get_value(), *value);
+            }
+            for (index, value) in &step.eliminations {
+                assert_ne!(
+                    solution.cells[*index as usize].get_value(),
+                    *value,
+                    "{}",
+                    step
+                );
+            }
+            step.apply(&mut board);
+        }
+        board.is_solved()
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(block_of(0), 0);
+        assert_eq!(block_of(40), 4);
+        assert_eq!(block_of(80), 8);
+        assert!(sees(0, 8));
+        assert!(sees(0, 72));
+        assert!(sees(0, 20));
+        assert!(!sees(0, 21));
+        assert!(!sees(0, 0));
+        assert_eq!(combinations(&[1, 2, 3, 4], 2).len(), 6);
+        assert_eq!(combinations(&[1, 2, 3, 4], 3)[0], vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_solve_without_guessing() {
+        // needs more than singles
+        let game =
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let v = game_str_to_vec(game).unwrap();
+        let mut board = Board::new();
+        board.load_candidates(&v);
+        let result = LogicSolver::new().solve(&mut board);
+        assert!(result.solved);
+        assert!(board.is_solved());
+        assert!(result
+            .techniques
+            .keys()
+            .any(|k| *k > TechniqueKind::NakedSingle));
+    }
+
+    #[test]
+    fn test_description() {
+        let step = SolveStep {
+            technique: TechniqueKind::NakedPair,
+            cells: vec![27, 30],
+            units: vec![UnitId::Row(3)],
+            digits: vec![3, 7],
+            placements: vec![],
+            eliminations: vec![(34, 3), (35, 3), (35, 7)],
+        };
+        assert_eq!(
+            step.to_string(),
+            "Naked pair {3,7} in row 4 removes 3 from r4c8, r4c9 and 7 from r4c9"
+        );
+
+        let step = SolveStep {
+            technique: TechniqueKind::XYWing,
+            cells: vec![0, 5, 36],
+            units: vec![],
+            digits: vec![1, 2, 3],
+            placements: vec![],
+            eliminations: vec![(41, 3)],
+        };
+        assert_eq!(
+            step.to_string(),
+            "XY-Wing {1,2,3} at r1c1, r1c6 and r5c1 removes 3 from r5c6"
+        );
+
+        let step = SolveStep {
+            technique: TechniqueKind::HiddenSingle,
+            cells: vec![10],
+            units: vec![UnitId::Block(0)],
+            digits: vec![9],
+            placements: vec![(10, 9)],
+            eliminations: vec![],
+        };
+        assert_eq!(
+            step.to_string(),
+            "Hidden single 9 in block 1 sets r2c2 to 9"
+        );
+    }
+
+    #[test]
+    fn test_solve_steps() {
+        let game =
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let v = game_str_to_vec(game).unwrap();
+        let mut board = Board::new();
+        board.load_candidates(&v);
+        let steps = LogicSolver::new().solve_steps(&mut board);
+        assert!(board.is_solved());
+        let placed: usize = steps.iter().map(|step| step.placements.len()).sum();
+        assert_eq!(placed, 81 - 17);
+        for step in &steps {
+            assert!(!step.cells.is_empty());
+            assert!(!step.digits.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_solve_is_sound() {
+        assert!(assert_sound(
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......"
+        ));
+        let mut generator = Generator::from_seed(7);
+        for _ in 0..10 {
+            let game = generator.generate_game();
+            let puzzle = generator.dig_holes(&game).unwrap();
+            assert_sound(&puzzle);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use super::{candidates, cells_with, digits, SolveStep, Technique, TechniqueKind};
+use crate::board::{Board, UnitId};
+
+// a cell that has only one candidate left
+pub struct NakedSingle;
+
+impl Technique for NakedSingle {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        for index in 0..81 {
+            let mask = candidates(board, index);
+            if mask.count_ones() == 1 {
+                let value = digits(mask)[0];
+                return Some(SolveStep {
+                    technique: TechniqueKind::NakedSingle,
+                    cells: vec![index],
+                    units: vec![],
+                    digits: vec![value],
+                    placements: vec![(index, value)],
+                    eliminations: vec![],
+                });
+            }
+        }
+        None
+    }
+}
+
+// a digit that fits in only one cell of a unit
+pub struct HiddenSingle;
+
+impl Technique for HiddenSingle {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        for unit in UnitId::all() {
+            for value in 1..=9 {
+                let cells = cells_with(board, unit.cells(), value);
+                if cells.len() == 1 {
+                    return Some(SolveStep {
+                        technique: TechniqueKind::HiddenSingle,
+                        cells: cells.clone(),
+                        units: vec![unit],
+                        digits: vec![value],
+                        placements: vec![(cells[0], value)],
+                        eliminations: vec![],
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HiddenSingle, NakedSingle};
+    use crate::board::Board;
+    use crate::logic::Technique;
+
+    #[test]
+    fn test_naked_single() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(NakedSingle.find(&b), None);
+        for value in 1..9 {
+            b.cells[10].remove_candidate(value);
+        }
+        let d = NakedSingle.find(&b).unwrap();
+        assert_eq!(d.placements, vec![(10, 9)]);
+    }
+
+    #[test]
+    fn test_hidden_single() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(HiddenSingle.find(&b), None);
+        for index in 1..9 {
+            b.cells[index].remove_candidate(4);
+        }
+        let d = HiddenSingle.find(&b).unwrap();
+        assert_eq!(d.placements, vec![(0, 4)]);
+    }
+}
@@ -0,0 +1,203 @@
+use super::{candidates, cells_with, combinations, digits, SolveStep, Technique, TechniqueKind};
+use crate::board::{Board, UnitId};
+
+/**
+ * N cells of a unit whose candidates are N digits in total: the digits must go in these
+ * cells, so they can be removed from the other cells of the unit.
+ */
+pub struct NakedSubset {
+    size: usize,
+}
+
+impl NakedSubset {
+    // size 2, 3 or 4 for pairs, triples and quads
+    pub fn new(size: usize) -> Self {
+        NakedSubset { size }
+    }
+
+    fn kind(&self) -> TechniqueKind {
+        match self.size {
+            2 => TechniqueKind::NakedPair,
+            3 => TechniqueKind::NakedTriple,
+            _ => TechniqueKind::NakedQuad,
+        }
+    }
+}
+
+impl Technique for NakedSubset {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        for unit_id in UnitId::all() {
+            let unit = unit_id.cells();
+            let cells: Vec<u8> = unit
+                .iter()
+                .copied()
+                .filter(|index| {
+                    let count = candidates(board, *index).count_ones() as usize;
+                    count >= 2 && count <= self.size
+                })
+                .collect();
+
+            for subset in combinations(&cells, self.size) {
+                let mask = subset
+                    .iter()
+                    .fold(0, |mask, index| mask | candidates(board, *index));
+                if mask.count_ones() as usize != self.size {
+                    continue;
+                }
+
+                let mut eliminations = Vec::new();
+                for index in unit.iter().filter(|index| !subset.contains(index)) {
+                    for value in digits(candidates(board, *index) & mask) {
+                        eliminations.push((*index, value));
+                    }
+                }
+                if !eliminations.is_empty() {
+                    return Some(SolveStep {
+                        technique: self.kind(),
+                        cells: subset,
+                        units: vec![unit_id],
+                        digits: digits(mask),
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+/**
+ * N digits that fit only in the same N cells of a unit: these cells can not hold any other
+ * digit.
+ */
+pub struct HiddenSubset {
+    size: usize,
+}
+
+impl HiddenSubset {
+    // size 2, 3 or 4 for pairs, triples and quads
+    pub fn new(size: usize) -> Self {
+        HiddenSubset { size }
+    }
+
+    fn kind(&self) -> TechniqueKind {
+        match self.size {
+            2 => TechniqueKind::HiddenPair,
+            3 => TechniqueKind::HiddenTriple,
+            _ => TechniqueKind::HiddenQuad,
+        }
+    }
+}
+
+impl Technique for HiddenSubset {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        for unit_id in UnitId::all() {
+            let unit = unit_id.cells();
+            let values: Vec<u8> = (1..=9)
+                .filter(|value| {
+                    let count = cells_with(board, unit, *value).len();
+                    count >= 2 && count <= self.size
+                })
+                .collect();
+
+            for subset in combinations(&values, self.size) {
+                let mut cells: Vec<u8> = subset
+                    .iter()
+                    .flat_map(|value| cells_with(board, unit, *value))
+                    .collect();
+                cells.sort_unstable();
+                cells.dedup();
+                if cells.len() != self.size {
+                    continue;
+                }
+
+                let mask = subset
+                    .iter()
+                    .fold(0u16, |mask, value| mask | 1 << (value - 1));
+                let mut eliminations = Vec::new();
+                for index in &cells {
+                    for value in digits(candidates(board, *index) & !mask) {
+                        eliminations.push((*index, value));
+                    }
+                }
+                if !eliminations.is_empty() {
+                    return Some(SolveStep {
+                        technique: self.kind(),
+                        cells,
+                        units: vec![unit_id],
+                        digits: subset,
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HiddenSubset, NakedSubset};
+    use crate::board::Board;
+    use crate::logic::{Technique, TechniqueKind};
+
+    #[test]
+    fn test_naked_pair() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(NakedSubset::new(2).find(&b), None);
+        for value in 3..=9 {
+            b.cells[0].remove_candidate(value);
+            b.cells[5].remove_candidate(value);
+        }
+        let d = NakedSubset::new(2).find(&b).unwrap();
+        assert_eq!(d.technique, TechniqueKind::NakedPair);
+        // row 1 is the first unit
+        assert_eq!(d.eliminations.len(), 14);
+        assert!(d.eliminations.contains(&(8, 1)));
+        assert!(d.eliminations.contains(&(1, 2)));
+        assert!(!d
+            .eliminations
+            .iter()
+            .any(|(index, _)| *index == 0 || *index == 5));
+    }
+
+    #[test]
+    fn test_naked_triple() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        // {1,2} {2,3} {1,3} in column 1
+        for value in 4..=9 {
+            b.cells[0].remove_candidate(value);
+            b.cells[36].remove_candidate(value);
+            b.cells[72].remove_candidate(value);
+        }
+        b.cells[0].remove_candidate(3);
+        b.cells[36].remove_candidate(1);
+        b.cells[72].remove_candidate(2);
+        assert_eq!(NakedSubset::new(2).find(&b), None);
+        let d = NakedSubset::new(3).find(&b).unwrap();
+        assert_eq!(d.technique, TechniqueKind::NakedTriple);
+        assert_eq!(d.eliminations.len(), 18);
+        assert!(d.eliminations.contains(&(9, 3)));
+    }
+
+    #[test]
+    fn test_hidden_pair() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(HiddenSubset::new(2).find(&b), None);
+        // 1 and 2 only fit in cells 3 and 7 of row 1
+        for index in [0, 1, 2, 4, 5, 6, 8] {
+            b.cells[index].remove_candidate(1);
+            b.cells[index].remove_candidate(2);
+        }
+        let d = HiddenSubset::new(2).find(&b).unwrap();
+        assert_eq!(d.technique, TechniqueKind::HiddenPair);
+        assert_eq!(d.eliminations.len(), 14);
+        assert!(d.eliminations.contains(&(3, 9)));
+        assert!(!d.eliminations.contains(&(7, 1)));
+    }
+}
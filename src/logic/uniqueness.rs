@@ -0,0 +1,197 @@
+use super::{
+    candidates, cells_with, col_of, common_peers_with, digits, row_of, SolveStep, Technique,
+    TechniqueKind,
+};
+use crate::board::{get_cell_units, Board};
+
+/**
+ * Four cells at the corners of a rectangle spanning two blocks can not all end up with the
+ * same two digits {a,b}, or the digits could be swapped and the puzzle would not be unique.
+ * Type 1: three corners are {a,b}, so the fourth can not be a or b.
+ * Type 2: two corners are {a,b} and the other two are {a,b,c}, so one of those is c and c
+ * can be removed from the cells seeing both of them.
+ */
+pub struct UniqueRectangle;
+
+impl Technique for UniqueRectangle {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        for r1 in 0..9u8 {
+            for r2 in r1 + 1..9 {
+                for c1 in 0..9u8 {
+                    for c2 in c1 + 1..9 {
+                        // the rectangle must span exactly two blocks
+                        if (r1 / 3 == r2 / 3) == (c1 / 3 == c2 / 3) {
+                            continue;
+                        }
+                        let corners = [r1 * 9 + c1, r1 * 9 + c2, r2 * 9 + c1, r2 * 9 + c2];
+                        if let Some(step) = check_rectangle(board, corners) {
+                            return Some(step);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+fn check_rectangle(board: &Board, corners: [u8; 4]) -> Option<SolveStep> {
+    let masks = corners.map(|index| candidates(board, index));
+    let pair = masks.iter().fold(0x1ff, |pair, mask| pair & mask);
+    if pair.count_ones() != 2 {
+        return None;
+    }
+    let floor: Vec<u8> = (0..4)
+        .filter(|i| masks[*i] == pair)
+        .map(|i| corners[i])
+        .collect();
+    let roof: Vec<u8> = (0..4)
+        .filter(|i| masks[*i] != pair)
+        .map(|i| corners[i])
+        .collect();
+
+    if floor.len() == 3 {
+        let eliminations = digits(pair)
+            .into_iter()
+            .map(|value| (roof[0], value))
+            .collect();
+        return Some(SolveStep {
+            technique: TechniqueKind::UniqueRectangle,
+            cells: corners.to_vec(),
+            units: vec![],
+            digits: digits(pair),
+            placements: vec![],
+            eliminations,
+        });
+    }
+
+    if floor.len() == 2 {
+        let same_line = row_of(roof[0]) == row_of(roof[1]) || col_of(roof[0]) == col_of(roof[1]);
+        let extra = candidates(board, roof[0]) & !pair;
+        if same_line && extra.count_ones() == 1 && candidates(board, roof[1]) & !pair == extra {
+            let value = digits(extra)[0];
+            let peers = common_peers_with(board, &roof, value);
+            if !peers.is_empty() {
+                return Some(SolveStep {
+                    technique: TechniqueKind::UniqueRectangle,
+                    cells: corners.to_vec(),
+                    units: vec![],
+                    digits: digits(pair | extra),
+                    placements: vec![],
+                    eliminations: peers.into_iter().map(|index| (index, value)).collect(),
+                });
+            }
+        }
+    }
+    None
+}
+
+/**
+ * Bivalue Universal Grave + 1: every empty cell has two candidates but one, which has three.
+ * Without that cell's extra digit the puzzle would have two solutions, so the digit that
+ * appears three times in the cell's units must be its value.
+ */
+pub struct BugPlusOne;
+
+impl Technique for BugPlusOne {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        let mut extra_cell: Option<u8> = None;
+        for index in 0..81 {
+            match candidates(board, index).count_ones() {
+                0 | 2 => {}
+                3 if extra_cell.is_none() => extra_cell = Some(index),
+                _ => return None,
+            }
+        }
+        let index = extra_cell?;
+
+        let units = get_cell_units(index);
+        for value in digits(candidates(board, index)) {
+            if units
+                .iter()
+                .all(|unit| cells_with(board, &unit[..], value).len() == 3)
+            {
+                return Some(SolveStep {
+                    technique: TechniqueKind::BugPlusOne,
+                    cells: vec![index],
+                    units: vec![],
+                    digits: vec![value],
+                    placements: vec![(index, value)],
+                    eliminations: vec![],
+                });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BugPlusOne, UniqueRectangle};
+    use crate::board::Board;
+    use crate::logic::Technique;
+
+    fn keep_candidates(b: &mut Board, index: usize, values: &[u8]) {
+        for value in 1..=9 {
+            if !values.contains(&value) {
+                b.cells[index].remove_candidate(value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unique_rectangle_type_1() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(UniqueRectangle.find(&b), None);
+        for index in [0, 3, 9] {
+            keep_candidates(&mut b, index, &[1, 2]);
+        }
+        keep_candidates(&mut b, 12, &[1, 2, 5]);
+        let d = UniqueRectangle.find(&b).unwrap();
+        assert_eq!(d.eliminations, vec![(12, 1), (12, 2)]);
+    }
+
+    #[test]
+    fn test_unique_rectangle_type_2() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        for index in [0, 3] {
+            keep_candidates(&mut b, index, &[1, 2]);
+        }
+        for index in [9, 12] {
+            keep_candidates(&mut b, index, &[1, 2, 5]);
+        }
+        let d = UniqueRectangle.find(&b).unwrap();
+        assert_eq!(
+            d.eliminations,
+            vec![
+                (10, 5),
+                (11, 5),
+                (13, 5),
+                (14, 5),
+                (15, 5),
+                (16, 5),
+                (17, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bug_plus_one() {
+        // each cell keeps its digit in two different solutions, so every digit appears twice
+        // in every unit, then cell 1 gets a third candidate
+        let solution =
+            "628519437579423618413786529857932146362147895194865273781354962235691784946278351";
+        let mut b = Board::new();
+        for (index, c) in solution.chars().enumerate() {
+            let value = c.to_digit(10).unwrap() as u8;
+            b.cells[index].add_candidate(value);
+            b.cells[index].add_candidate(value % 9 + 1);
+        }
+        assert_eq!(BugPlusOne.find(&b), None);
+        b.cells[0].add_candidate(1);
+        let d = BugPlusOne.find(&b).unwrap();
+        assert_eq!(d.placements, vec![(0, 1)]);
+    }
+}
@@ -0,0 +1,232 @@
+use super::{
+    candidates, cells_with, common_peers_with, digits, sees, SolveStep, Technique, TechniqueKind,
+};
+use crate::board::{Board, UnitId};
+
+fn bivalue_cells(board: &Board) -> Vec<u8> {
+    (0..81)
+        .filter(|index| candidates(board, *index).count_ones() == 2)
+        .collect()
+}
+
+// a step removing a digit from the targets, if there are any
+fn eliminate(
+    technique: TechniqueKind,
+    cells: Vec<u8>,
+    units: Vec<UnitId>,
+    digits: Vec<u8>,
+    targets: Vec<u8>,
+    value: u8,
+) -> Option<SolveStep> {
+    if targets.is_empty() {
+        return None;
+    }
+    Some(SolveStep {
+        technique,
+        cells,
+        units,
+        digits,
+        placements: vec![],
+        eliminations: targets.into_iter().map(|index| (index, value)).collect(),
+    })
+}
+
+/**
+ * A pivot cell {x,y} seeing two pincers {x,z} and {y,z}: whatever the pivot is, one of the
+ * pincers is z, so z can be removed from the cells seeing both pincers.
+ */
+pub struct XYWing;
+
+impl Technique for XYWing {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        let cells = bivalue_cells(board);
+        for pivot in &cells {
+            let pivot_mask = candidates(board, *pivot);
+            let pincers: Vec<u8> = cells
+                .iter()
+                .copied()
+                .filter(|index| sees(*pivot, *index))
+                .filter(|index| (candidates(board, *index) & pivot_mask).count_ones() == 1)
+                .collect();
+
+            for (i, a) in pincers.iter().enumerate() {
+                for b in &pincers[i + 1..] {
+                    let mask_a = candidates(board, *a);
+                    let mask_b = candidates(board, *b);
+                    let z = mask_a & mask_b;
+                    // the pincers share z, and hold both digits of the pivot between them
+                    if z.count_ones() != 1 || z & pivot_mask != 0 {
+                        continue;
+                    }
+                    if (mask_a | mask_b) & pivot_mask != pivot_mask {
+                        continue;
+                    }
+                    let value = digits(z)[0];
+                    let peers = common_peers_with(board, &[*a, *b], value);
+                    let step = eliminate(
+                        TechniqueKind::XYWing,
+                        vec![*pivot, *a, *b],
+                        vec![],
+                        digits(pivot_mask | z),
+                        peers,
+                        value,
+                    );
+                    if step.is_some() {
+                        return step;
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/**
+ * A pivot cell {x,y,z} seeing two pincers {x,z} and {y,z}: one of the three cells is z, so
+ * z can be removed from the cells seeing all of them.
+ */
+pub struct XYZWing;
+
+impl Technique for XYZWing {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        let cells = bivalue_cells(board);
+        for pivot in 0..81 {
+            let pivot_mask = candidates(board, pivot);
+            if pivot_mask.count_ones() != 3 {
+                continue;
+            }
+            let pincers: Vec<u8> = cells
+                .iter()
+                .copied()
+                .filter(|index| sees(pivot, *index))
+                .filter(|index| candidates(board, *index) & !pivot_mask == 0)
+                .collect();
+
+            for (i, a) in pincers.iter().enumerate() {
+                for b in &pincers[i + 1..] {
+                    let mask_a = candidates(board, *a);
+                    let mask_b = candidates(board, *b);
+                    if mask_a == mask_b {
+                        continue;
+                    }
+                    let value = digits(mask_a & mask_b)[0];
+                    let peers = common_peers_with(board, &[pivot, *a, *b], value);
+                    let step = eliminate(
+                        TechniqueKind::XYZWing,
+                        vec![pivot, *a, *b],
+                        vec![],
+                        digits(pivot_mask),
+                        peers,
+                        value,
+                    );
+                    if step.is_some() {
+                        return step;
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/**
+ * Two cells {x,y} that do not see each other, linked by a unit where x fits only in a cell
+ * seeing the first and a cell seeing the second: one of the two cells is y, so y can be
+ * removed from the cells seeing both.
+ */
+pub struct WWing;
+
+impl Technique for WWing {
+    fn find(&self, board: &Board) -> Option<SolveStep> {
+        let cells = bivalue_cells(board);
+        for (i, a) in cells.iter().enumerate() {
+            let mask = candidates(board, *a);
+            for b in &cells[i + 1..] {
+                if candidates(board, *b) != mask || sees(*a, *b) {
+                    continue;
+                }
+                let pair = digits(mask);
+                for (x, y) in [(pair[0], pair[1]), (pair[1], pair[0])] {
+                    for unit in UnitId::all() {
+                        let link = cells_with(board, unit.cells(), x);
+                        if link.len() != 2 || link.contains(a) || link.contains(b) {
+                            continue;
+                        }
+                        let linked = (sees(link[0], *a) && sees(link[1], *b))
+                            || (sees(link[1], *a) && sees(link[0], *b));
+                        if !linked {
+                            continue;
+                        }
+                        let peers = common_peers_with(board, &[*a, *b], y);
+                        let step = eliminate(
+                            TechniqueKind::WWing,
+                            vec![*a, *b, link[0], link[1]],
+                            vec![unit],
+                            vec![x, y],
+                            peers,
+                            y,
+                        );
+                        if step.is_some() {
+                            return step;
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WWing, XYWing, XYZWing};
+    use crate::board::Board;
+    use crate::logic::Technique;
+
+    fn keep_candidates(b: &mut Board, index: usize, values: &[u8]) {
+        for value in 1..=9 {
+            if !values.contains(&value) {
+                b.cells[index].remove_candidate(value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_xy_wing() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(XYWing.find(&b), None);
+        keep_candidates(&mut b, 0, &[1, 2]);
+        keep_candidates(&mut b, 5, &[1, 3]);
+        keep_candidates(&mut b, 36, &[2, 3]);
+        let d = XYWing.find(&b).unwrap();
+        assert_eq!(d.eliminations, vec![(41, 3)]);
+    }
+
+    #[test]
+    fn test_xyz_wing() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        assert_eq!(XYZWing.find(&b), None);
+        keep_candidates(&mut b, 0, &[1, 2, 3]);
+        keep_candidates(&mut b, 1, &[1, 3]);
+        keep_candidates(&mut b, 27, &[2, 3]);
+        let d = XYZWing.find(&b).unwrap();
+        assert_eq!(d.eliminations, vec![(9, 3), (18, 3)]);
+    }
+
+    #[test]
+    fn test_w_wing() {
+        let mut b = Board::new();
+        b.fill_candidates();
+        keep_candidates(&mut b, 0, &[1, 2]);
+        keep_candidates(&mut b, 40, &[1, 2]);
+        assert_eq!(WWing.find(&b), None);
+        // 1 of the last row only fits in columns 1 and 5
+        for col in [1, 2, 3, 5, 6, 7, 8] {
+            b.cells[72 + col].remove_candidate(1);
+        }
+        let d = WWing.find(&b).unwrap();
+        assert_eq!(d.eliminations, vec![(4, 2), (36, 2)]);
+    }
+}
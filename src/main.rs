@@ -1,30 +1,110 @@
 use cursive::event::Key;
-use cursive::views::{Dialog, LinearLayout, Panel};
+use cursive::menu;
+use cursive::views::{Dialog, LinearLayout, NamedView, Panel};
 use cursive::Cursive;
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::sync::Arc;
+use rand::thread_rng;
+use std::fs;
+use std::sync::{Arc, RwLock};
+use sudoku_generator::generator::Generator;
+use sudoku_generator::grid::{Geometry, Grid};
+use sudoku_generator::hint::{hint, Hint, Reveal};
+use sudoku_generator::killer::{self, Cage};
+use sudoku_generator::variant::Variant;
+use sudoku_generator::{board, cell, pencilmarks};
 
-mod board;
 mod boardview;
-mod cell;
 mod cellview;
-pub mod generator;
+
+type SharedCells = Arc<Vec<Arc<RwLock<cell::Cell>>>>;
+type SharedGame = Arc<RwLock<Game>>;
+
+// what is being played: a classic game with the extra units of some variants, a jigsaw or a killer
+struct Game {
+    variants: Vec<Variant>,
+    jigsaw: bool,
+    killer: bool,
+    // the regions of the current game, the blocks unless it is a jigsaw
+    regions: Geometry,
+    // the cages of the current game if it is a killer
+    cages: Vec<Cage>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game {
+            variants: Vec::new(),
+            jigsaw: false,
+            killer: false,
+            regions: Geometry::classic(),
+            cages: Vec::new(),
+        }
+    }
+}
+
+const PENCIL_MARKS_FILE: &str = "sudoku.pm.txt";
+
+const BOARD_VIEW: &str = "board";
+
+// the entries of the "Variant" menu
+const VARIANTS: [(&str, &[Variant]); 4] = [
+    ("Classic", &[]),
+    ("Sudoku-X", &[Variant::Diagonal]),
+    ("Windoku", &[Variant::Windoku]),
+    ("Windoku-X", &[Variant::Diagonal, Variant::Windoku]),
+];
 
 fn main() {
-    let mut cells: Arc<Vec<Rc<RefCell<cell::Cell>>>> = Arc::new(Vec::new());
+    let mut cells: SharedCells = Arc::new(Vec::new());
 
     for _ in 0..81 {
-        let cell = Rc::new(RefCell::new(cell::Cell::new()));
-        Arc::get_mut(&mut cells).unwrap().push(Rc::clone(&cell));
+        let cell = Arc::new(RwLock::new(cell::Cell::new()));
+        Arc::get_mut(&mut cells).unwrap().push(Arc::clone(&cell));
     }
 
+    let game: SharedGame = Arc::new(RwLock::new(Game::default()));
+
     let new_cells = Arc::clone(&cells);
+    let new_game_state = Arc::clone(&game);
     let reset_cells = Arc::clone(&cells);
     let check_cells = Arc::clone(&cells);
+    let check_game_state = Arc::clone(&game);
+    let hint_cells = Arc::clone(&cells);
+    let hint_game_state = Arc::clone(&game);
+    let export_cells = Arc::clone(&cells);
+
+    let mut variant_menu = menu::Tree::new();
+    for (name, choice) in VARIANTS {
+        let cells = Arc::clone(&cells);
+        let game = Arc::clone(&game);
+        variant_menu.add_leaf(name, move |s| {
+            *game.write().unwrap() = Game {
+                variants: choice.to_vec(),
+                ..Game::default()
+            };
+            new_game(s, &cells, &game);
+        });
+    }
+    let jigsaw_cells = Arc::clone(&cells);
+    let jigsaw_game = Arc::clone(&game);
+    variant_menu.add_leaf("Jigsaw", move |s| {
+        *jigsaw_game.write().unwrap() = Game {
+            jigsaw: true,
+            ..Game::default()
+        };
+        new_game(s, &jigsaw_cells, &jigsaw_game);
+    });
+    let killer_cells = Arc::clone(&cells);
+    let killer_game = Arc::clone(&game);
+    variant_menu.add_leaf("Killer", move |s| {
+        *killer_game.write().unwrap() = Game {
+            killer: true,
+            ..Game::default()
+        };
+        new_game(s, &killer_cells, &killer_game);
+    });
 
     let mut siv = cursive::default();
-    let bv = boardview::BoardView::new(Arc::clone(&cells));
+    let bv = NamedView::new(BOARD_VIEW, boardview::BoardView::new(Arc::clone(&cells)));
     siv.add_layer(
         Dialog::new()
             .title("Sudoku")
@@ -32,14 +112,21 @@ fn main() {
     );
 
     siv.menubar()
-        .add_leaf("New", move |_| {
-            new_game(&new_cells);
+        .add_leaf("New", move |s| {
+            new_game(s, &new_cells, &new_game_state);
         })
+        .add_subtree("Variant", variant_menu)
         .add_leaf("Reset", move |_| {
             reset_game(&reset_cells);
         })
         .add_leaf("Check", move |s| {
-            check_game(s, &check_cells);
+            check_game(s, &check_cells, &check_game_state);
+        })
+        .add_leaf("Hint", move |s| {
+            hint_game(s, &hint_cells, &hint_game_state);
+        })
+        .add_leaf("Export", move |s| {
+            export_game(s, &export_cells);
         })
         .add_leaf("Quit", |s| {
             s.quit();
@@ -53,16 +140,36 @@ fn main() {
     siv.run();
 }
 
-fn new_game(cells: &Arc<Vec<Rc<RefCell<cell::Cell>>>>) {
-    let game = generator::generate_game();
-    let puzzle = generator::dig_holes(&game);
-    let game_vec = board::game_str_to_vec(&puzzle).unwrap();
-    for index in 0..81 {
-        let c = &cells[index];
-        let mut item = c.try_borrow_mut().unwrap();
+// a new puzzle of the game being played, with a new layout for a jigsaw and new cages for a killer
+fn new_game(s: &mut Cursive, cells: &SharedCells, game: &SharedGame) {
+    let mut game = game.write().unwrap();
+    let mut generator = Generator::new(thread_rng()).with_variants(&game.variants);
+    let game_vec = if game.jigsaw {
+        game.regions = generator.generate_jigsaw(9).unwrap();
+        // a layout of generate_jigsaw has a solution
+        let solution = generator.generate_grid(&game.regions).unwrap();
+        generator.dig_grid(&solution).unwrap().values().to_vec()
+    } else if game.killer {
+        game.regions = Geometry::classic();
+        let (puzzle, cages) = generator.generate_killer();
+        game.cages = cages;
+        board::game_str_to_vec(&puzzle).unwrap()
+    } else {
+        game.regions = Geometry::classic();
+        let solution = generator.generate_game();
+        let puzzle = generator.dig_holes(&solution).unwrap();
+        board::game_str_to_vec(&puzzle).unwrap()
+    };
+    s.call_on_name(BOARD_VIEW, |bv: &mut boardview::BoardView| {
+        bv.set_variants(&game.variants);
+        bv.set_regions(game.regions.regions());
+        bv.set_cages(&game.cages);
+    });
+    for (c, value) in cells.iter().zip(game_vec) {
+        let mut item = c.write().unwrap();
         item.reset();
-        if game_vec[index] > 0 {
-            item.set_value(game_vec[index]);
+        if value > 0 {
+            item.set_value(value);
             item.set_readonly(true);
         } else {
             item.fill_candidates();
@@ -70,10 +177,9 @@ fn new_game(cells: &Arc<Vec<Rc<RefCell<cell::Cell>>>>) {
     }
 }
 
-fn reset_game(cells: &Arc<Vec<Rc<RefCell<cell::Cell>>>>) {
-    for index in 0..81 {
-        let c = &cells[index];
-        let mut item = c.try_borrow_mut().unwrap();
+fn reset_game(cells: &SharedCells) {
+    for c in cells.iter() {
+        let mut item = c.write().unwrap();
 
         if !item.is_readonly() {
             item.reset();
@@ -82,12 +188,11 @@ fn reset_game(cells: &Arc<Vec<Rc<RefCell<cell::Cell>>>>) {
     }
 }
 
-fn check_game(s: &mut Cursive, cells: &Arc<Vec<Rc<RefCell<cell::Cell>>>>) {
+fn check_game(s: &mut Cursive, cells: &SharedCells, state: &SharedGame) {
     let mut game = Vec::new();
 
-    for index in 0..81 {
-        let c = &cells[index];
-        let item = c.try_borrow().unwrap();
+    for c in cells.iter() {
+        let item = c.read().unwrap();
 
         if item.is_fixed() {
             game.push(item.get_value());
@@ -95,11 +200,27 @@ fn check_game(s: &mut Cursive, cells: &Arc<Vec<Rc<RefCell<cell::Cell>>>>) {
             game.push(0);
         }
     }
-    let mut board = board::Board::new();
-    board.load_game(&game);
+    let state = state.read().unwrap();
+    let solved = if state.jigsaw {
+        let mut grid = Grid::new(state.regions.clone());
+        for (i, value) in game.into_iter().enumerate() {
+            grid.set_value(i, value);
+        }
+        grid.is_solved()
+    } else {
+        let mut board = board::Board::new();
+        for variant in &state.variants {
+            board.add_variant(*variant);
+        }
+        for cage in killer::constraints(&state.cages) {
+            board.add_constraint(cage);
+        }
+        board.load_game(&game).unwrap();
+        board.is_solved()
+    };
 
     s.add_layer(
-        Dialog::text(match board.is_solved() {
+        Dialog::text(match solved {
             true => "Game solved.",
             _ => "Game not solved.",
         })
@@ -109,3 +230,75 @@ fn check_game(s: &mut Cursive, cells: &Arc<Vec<Rc<RefCell<cell::Cell>>>>) {
         }),
     );
 }
+
+fn current_board(cells: &SharedCells) -> board::Board {
+    let mut board = board::Board::new();
+    for (i, c) in cells.iter().enumerate() {
+        board.cells[i] = *c.read().unwrap();
+    }
+    board
+}
+
+fn hint_game(s: &mut Cursive, cells: &SharedCells, game: &SharedGame) {
+    // the logic solver only knows the units and digits of the classic board
+    let (variants, jigsaw, killer) = {
+        let game = game.read().unwrap();
+        (!game.variants.is_empty(), game.jigsaw, game.killer)
+    };
+    let text = match (variants, jigsaw, killer) {
+        (true, _, _) => "No hints for Sudoku-X or Windoku puzzles.".to_string(),
+        (_, true, _) => "No hints for jigsaw puzzles.".to_string(),
+        (_, _, true) => "No hints for killer puzzles.".to_string(),
+        _ => match hint(&current_board(cells)) {
+            Ok(Some(h)) => return show_hint(s, h, Reveal::Region),
+            Ok(None) => "No hint available.".to_string(),
+            Err(error) => format!("No hint: {error}."),
+        },
+    };
+    s.add_layer(Dialog::text(text).title("Hint").button("Ok", |s| {
+        s.pop_layer();
+    }));
+}
+
+// show one level of the hint, "More" reveals the next one
+fn show_hint(s: &mut Cursive, h: Hint, level: Reveal) {
+    let next = match level {
+        Reveal::Region => Some(Reveal::Technique),
+        Reveal::Technique => Some(Reveal::Placement),
+        Reveal::Placement => None,
+    };
+    let mut dialog = Dialog::text(h.reveal(level)).title("Hint");
+    if let (Hint::Step(_), Some(next)) = (&h, next) {
+        let h = h.clone();
+        dialog = dialog.button("More", move |s| {
+            s.pop_layer();
+            show_hint(s, h.clone(), next);
+        });
+    }
+    s.add_layer(dialog.button("Ok", |s| {
+        s.pop_layer();
+    }));
+}
+
+// show the pencil-mark grid of the game, "Save" writes it to PENCIL_MARKS_FILE
+fn export_game(s: &mut Cursive, cells: &SharedCells) {
+    let grid = pencilmarks::write_grid(&current_board(cells));
+    let saved = grid.clone();
+    s.add_layer(
+        Dialog::text(grid)
+            .title("Pencil marks")
+            .button("Save", move |s| {
+                s.pop_layer();
+                let text = match fs::write(PENCIL_MARKS_FILE, &saved) {
+                    Ok(()) => format!("Saved to {PENCIL_MARKS_FILE}."),
+                    Err(error) => format!("Could not save: {error}."),
+                };
+                s.add_layer(Dialog::text(text).title("Export").button("Ok", |s| {
+                    s.pop_layer();
+                }));
+            })
+            .button("Ok", |s| {
+                s.pop_layer();
+            }),
+    );
+}
@@ -0,0 +1,258 @@
+//! Reading games from text. A game lists its cells row by row, a digit for a given and one of
+//! `.`, `0`, `_`, `*` or `-` for an empty cell. Lenient parsing also reads games laid out as a
+//! grid, such as:
+//!
+//! ```text
+//! 4 . . | . . . | 8 . 5
+//! . 3 . | . . . | . . .
+//! . . . | 7 . . | . . .
+//! ------+-------+------
+//! ...
+//! ```
+//!
+//! Grids of other sizes are read the same way by `parse_grid`, with letters from `A` for the
+//! digits above 9.
+
+use crate::error::SudokuError;
+use crate::grid::Geometry;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    // exactly 81 digits and blanks, anything else is an invalid character
+    #[default]
+    Strict,
+    // whitespace and the grid characters `|` and `+` are skipped, and so are lines drawn with
+    // `-`, `+`, `|` and `=` only, unless a `-` stands alone between spaces or bars or the line
+    // is a row of as many `-` as the grid is wide: such a `-` and a `-` anywhere else are blanks
+    Lenient,
+}
+
+fn is_blank(c: char) -> bool {
+    matches!(c, '.' | '0' | '_' | '*' | '-')
+}
+
+/**
+ * A line such as `------+-------+------` or `--- --- ---`, not a row of blanks of a grid of
+ * side `side` such as `- - - | - - - | - - -`, `---------` or `---|---|---`.
+ */
+fn is_separator_line(line: &str, side: usize) -> bool {
+    let drawn = line.contains(['-', '='])
+        && line
+            .chars()
+            .all(|c| "-+|=".contains(c) || c.is_whitespace())
+        && !line
+            .split(|c: char| c == '|' || c.is_whitespace())
+            .any(|token| token == "-");
+    drawn
+        && (line.contains(['+', '='])
+            || line.split_whitespace().count() > 1
+            || line.matches('-').count() != side)
+}
+
+/**
+ * Read a game as 81 values, 0 for the empty cells. The position of an invalid character is
+ * counted in characters from the start of the input.
+ */
+pub fn parse_game(input: &str, mode: ParseMode) -> Result<Vec<u8>, SudokuError> {
+    parse_cells(input, mode, 9, &|c| match c.to_digit(10) {
+        Some(value @ 1..=9) => Some(value as u8),
+        _ => None,
+    })
+}
+
+/**
+ * Like `parse_game`, for a grid of any geometry, the digits above 9 being letters in either
+ * case.
+ */
+pub fn parse_grid(
+    input: &str,
+    mode: ParseMode,
+    geometry: &Geometry,
+) -> Result<Vec<u8>, SudokuError> {
+    parse_cells(input, mode, geometry.size(), &|c| geometry.char_digit(c))
+}
+
+// the cells of a grid of side `side`
+fn parse_cells(
+    input: &str,
+    mode: ParseMode,
+    side: usize,
+    digit: &dyn Fn(char) -> Option<u8>,
+) -> Result<Vec<u8>, SudokuError> {
+    let cells = side * side;
+    let mut result = Vec::with_capacity(cells);
+    let mut position = 0;
+    for line in input.split_inclusive('\n') {
+        let skip_line = mode == ParseMode::Lenient && is_separator_line(line, side);
+        for c in line.chars() {
+            if skip_line
+                || (mode == ParseMode::Lenient && (c.is_whitespace() || c == '|' || c == '+'))
+            {
+                // drawing only
+            } else if is_blank(c) {
+                result.push(0);
+            } else if let Some(value) = digit(c) {
+                result.push(value);
+            } else {
+                return Err(SudokuError::InvalidCharacter { position, char: c });
+            }
+            position += 1;
+        }
+    }
+    if result.len() != cells {
+        return Err(SudokuError::InvalidLength {
+            expected: cells,
+            found: result.len(),
+        });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_game, parse_grid, ParseMode};
+    use crate::board::Board;
+    use crate::error::SudokuError;
+    use crate::grid::Geometry;
+
+    const GAME: &str =
+        "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+
+    const GRID: &str = "
+4 . . | . . . | 8 . 5
+. 3 . | . . . | . . .
+. . . | 7 . . | . . .
+------+-------+------
+. 2 . | . . . | . 6 .
+. . . | . 8 . | 4 . .
+. . . | . 1 . | . . .
+------+-------+------
+. . . | 6 . 3 | . 7 .
+5 . . | 2 . . | . . .
+1 . 4 | . . . | . . .
+";
+
+    #[test]
+    fn test_strict() {
+        let game = parse_game(GAME, ParseMode::Strict).unwrap();
+        assert_eq!(game[0], 4);
+        assert_eq!(game[80], 0);
+        let other = GAME
+            .replace('.', "0")
+            .replacen('0', "_", 3)
+            .replacen('0', "*", 3);
+        assert_eq!(parse_game(&other, ParseMode::Strict).unwrap(), game);
+        let other = GAME.replace('.', "-");
+        assert_eq!(parse_game(&other, ParseMode::Strict).unwrap(), game);
+
+        assert_eq!(
+            parse_game(&GAME.replacen('3', "x", 1), ParseMode::Strict),
+            Err(SudokuError::InvalidCharacter {
+                position: 10,
+                char: 'x'
+            })
+        );
+        assert_eq!(
+            parse_game(&format!("{GAME}\n"), ParseMode::Strict),
+            Err(SudokuError::InvalidCharacter {
+                position: 81,
+                char: '\n'
+            })
+        );
+        assert_eq!(
+            parse_game(GRID, ParseMode::Strict),
+            Err(SudokuError::InvalidCharacter {
+                position: 0,
+                char: '\n'
+            })
+        );
+    }
+
+    #[test]
+    fn test_lenient() {
+        let game = parse_game(GAME, ParseMode::Strict).unwrap();
+        assert_eq!(parse_game(GRID, ParseMode::Lenient).unwrap(), game);
+        let spaced = format!(" {}\n{} \n", &GAME[..40], &GAME[40..]);
+        assert_eq!(parse_game(&spaced, ParseMode::Lenient).unwrap(), game);
+        // a blank row written with dashes is not a separator
+        let dashes = GRID.replace(". 3 . | . . . | . . .", "- 3 - | - - - | - - -");
+        assert_eq!(parse_game(&dashes, ParseMode::Lenient).unwrap(), game);
+        let blank_row = GRID.replace(". . . | . 1 . | . . .", "- - - | - - - | - - -");
+        let mut expected = game.clone();
+        expected[49] = 0;
+        assert_eq!(
+            parse_game(&blank_row, ParseMode::Lenient).unwrap(),
+            expected
+        );
+        // and neither is a compact one, unless it is longer than a row
+        let compact = GRID.replace(". . . | . 1 . | . . .", "---------");
+        assert_eq!(parse_game(&compact, ParseMode::Lenient).unwrap(), expected);
+        let compact = GRID.replace(". . . | . 1 . | . . .", "---|---|---");
+        assert_eq!(parse_game(&compact, ParseMode::Lenient).unwrap(), expected);
+        let long = GRID.replace("------+-------+------", "---------------------");
+        assert_eq!(parse_game(&long, ParseMode::Lenient).unwrap(), game);
+        let separators = GRID.replace("------+-------+------", "--- --- ---\n=== === ===");
+        assert_eq!(parse_game(&separators, ParseMode::Lenient).unwrap(), game);
+
+        assert_eq!(
+            parse_game(&GRID.replacen('7', "x", 1), ParseMode::Lenient),
+            Err(SudokuError::InvalidCharacter {
+                position: 53,
+                char: 'x'
+            })
+        );
+        assert_eq!(
+            parse_game(&GAME[1..], ParseMode::Lenient),
+            Err(SudokuError::InvalidLength {
+                expected: 81,
+                found: 80
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut board = Board::new();
+        board
+            .load_game(&parse_game(GRID, ParseMode::Lenient).unwrap())
+            .unwrap();
+        let serialized = board.serialize();
+        assert_eq!(serialized, GAME);
+        for mode in [ParseMode::Strict, ParseMode::Lenient] {
+            let mut other = Board::new();
+            other
+                .load_game(&parse_game(&serialized, mode).unwrap())
+                .unwrap();
+            assert_eq!(other.serialize(), serialized);
+        }
+    }
+
+    #[test]
+    fn test_parse_grid() {
+        let geometry = Geometry::for_size(16).unwrap();
+        let line = format!("1A.g{}", ".".repeat(252));
+        let grid = parse_grid(&line, ParseMode::Strict, &geometry).unwrap();
+        assert_eq!(grid[..4], [1, 10, 0, 16]);
+        assert_eq!(
+            parse_grid(&line.replacen('g', "h", 1), ParseMode::Strict, &geometry),
+            Err(SudokuError::InvalidCharacter {
+                position: 3,
+                char: 'h'
+            })
+        );
+
+        let geometry = Geometry::new(2, 2).unwrap();
+        let grid = "1 . | . 4\n. . | 1 .\n----+----\n. 1 | . .\n4 . | . 1\n";
+        assert_eq!(
+            parse_grid(grid, ParseMode::Lenient, &geometry).unwrap(),
+            [1, 0, 0, 4, 0, 0, 1, 0, 0, 1, 0, 0, 4, 0, 0, 1]
+        );
+        assert_eq!(
+            parse_grid(&"1".repeat(81), ParseMode::Strict, &geometry),
+            Err(SudokuError::InvalidLength {
+                expected: 16,
+                found: 81
+            })
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Games with their pencil marks, as the player sees them. Two layouts are read and written:
+//!
+//! - a candidate string of 729 characters, nine per cell, where the k-th character of a cell is
+//!   the digit k when k is possible there and `.` otherwise,
+//! - a pencil-mark grid in the style of HoDoKu and SudokuWiki, one token per cell:
+//!
+//! ```text
+//! .-----------------.-----------------.-----------------.
+//! | 4    1679  12679 | 139  2369  1369 | 8    1239  5    |
+//! ...
+//! '-----------------'-----------------'-----------------'
+//! ```
+//!
+//! A cell with a single digit is read as placed, and a cell without any pencil mark is written
+//! `.` in a grid. A grid writes a single candidate twice, such as `77`, so that it reads back as
+//! a candidate, which a candidate string cannot tell from a placed value. Which cells are givens
+//! is not kept.
+
+use crate::board::Board;
+use crate::error::SudokuError;
+use crate::logic::digits;
+
+// the digits of a cell, its value if placed
+fn marks(board: &Board, index: usize) -> Vec<u8> {
+    let cell = &board.cells[index];
+    if cell.is_fixed() {
+        vec![cell.get_value()]
+    } else {
+        digits(cell.get_candidates())
+    }
+}
+
+// set a cell from its digits as read, a single digit written alone is a value
+fn set_marks(board: &mut Board, index: usize, mask: u16, alone: bool) {
+    if alone && mask.count_ones() == 1 {
+        board.cells[index].restore((mask.trailing_zeros() as u8 + 1, 0));
+    } else {
+        board.cells[index].restore((0, mask));
+    }
+}
+
+pub fn write_candidates(board: &Board) -> String {
+    let mut result = String::with_capacity(729);
+    for index in 0..81 {
+        let marks = marks(board, index);
+        for digit in 1..=9 {
+            result.push(if marks.contains(&digit) {
+                char::from(b'0' + digit)
+            } else {
+                '.'
+            });
+        }
+    }
+    result
+}
+
+pub fn write_grid(board: &Board) -> String {
+    let tokens: Vec<String> = (0..81)
+        .map(|index| {
+            let marks = marks(board, index);
+            if marks.is_empty() {
+                ".".to_string()
+            } else if marks.len() == 1 && !board.cells[index].is_fixed() {
+                char::from(b'0' + marks[0]).to_string().repeat(2)
+            } else {
+                marks.iter().map(|digit| char::from(b'0' + digit)).collect()
+            }
+        })
+        .collect();
+    let mut widths = [0; 9];
+    for (index, token) in tokens.iter().enumerate() {
+        widths[index % 9] = widths[index % 9].max(token.len());
+    }
+
+    let border = |corner: char, middle: char| -> String {
+        let mut line = String::new();
+        for stack in 0..3 {
+            line.push(if stack == 0 { corner } else { middle });
+            let width: usize = widths[stack * 3..stack * 3 + 3].iter().sum();
+            line.push_str(&"-".repeat(width + 6));
+        }
+        line.push(corner);
+        line.push('\n');
+        line
+    };
+
+    let mut result = border('.', '.');
+    for row in 0..9 {
+        if row == 3 || row == 6 {
+            result.push_str(&border(':', '+'));
+        }
+        for col in 0..9 {
+            if col % 3 == 0 {
+                result.push_str("| ");
+            }
+            let token = &tokens[row * 9 + col];
+            result.push_str(token);
+            let gap = if col % 3 == 2 { 1 } else { 2 };
+            result.push_str(&" ".repeat(widths[col] - token.len() + gap));
+        }
+        result.push_str("|\n");
+    }
+    result.push_str(&border('\'', '\''));
+    result
+}
+
+/**
+ * Read pencil marks written either way, a candidate string being recognised by its 729
+ * characters. Whitespace around a candidate string is ignored.
+ */
+pub fn read_pencil_marks(input: &str) -> Result<Board, SudokuError> {
+    let trimmed = input.trim();
+    if trimmed.chars().count() == 729 && !trimmed.contains(['|', ' ']) {
+        let offset = input.len() - input.trim_start().len();
+        read_candidates(trimmed).map_err(|error| match error {
+            SudokuError::InvalidCharacter { position, char } => SudokuError::InvalidCharacter {
+                position: position + input[..offset].chars().count(),
+                char,
+            },
+            error => error,
+        })
+    } else {
+        read_grid(input)
+    }
+}
+
+pub fn read_candidates(input: &str) -> Result<Board, SudokuError> {
+    let length = input.chars().count();
+    if length != 729 {
+        return Err(SudokuError::InvalidLength {
+            expected: 729,
+            found: length,
+        });
+    }
+    let mut board = Board::new();
+    let mut mask = 0;
+    for (position, c) in input.chars().enumerate() {
+        let digit = position as u32 % 9 + 1;
+        match c {
+            '.' | '0' => {}
+            _ if c.to_digit(10) == Some(digit) => mask |= 1 << (digit - 1),
+            _ => return Err(SudokuError::InvalidCharacter { position, char: c }),
+        }
+        if digit == 9 {
+            set_marks(&mut board, position / 9, mask, true);
+            mask = 0;
+        }
+    }
+    Ok(board)
+}
+
+/**
+ * Read a pencil-mark grid. Lines drawn with `-` only, besides the corners and `|`, are borders,
+ * every other line has the tokens of a row between `|` and whitespace.
+ */
+pub fn read_grid(input: &str) -> Result<Board, SudokuError> {
+    let mut board = Board::new();
+    let mut cells = 0;
+    let mut position = 0;
+    for line in input.split_inclusive('\n') {
+        let border = line.contains('-')
+            && line
+                .chars()
+                .all(|c| ".-:+'|=".contains(c) || c.is_whitespace());
+        if border {
+            position += line.chars().count();
+            continue;
+        }
+
+        // the digits of the token being read and its length
+        let mut mask: Option<u16> = None;
+        let mut length = 0;
+        for c in line.chars().chain([' ']) {
+            if c.is_whitespace() || c == '|' {
+                if let Some(mask) = mask.take() {
+                    if cells < 81 {
+                        set_marks(&mut board, cells, mask, length == 1);
+                    }
+                    cells += 1;
+                    length = 0;
+                }
+            } else if c == '.' || c == '0' {
+                mask = Some(mask.unwrap_or(0));
+                length += 1;
+            } else if let Some(digit @ 1..=9) = c.to_digit(10) {
+                mask = Some(mask.unwrap_or(0) | 1 << (digit - 1));
+                length += 1;
+            } else {
+                return Err(SudokuError::InvalidCharacter { position, char: c });
+            }
+            position += 1;
+        }
+        // the space chained above is not part of the input
+        position -= 1;
+    }
+    if cells != 81 {
+        return Err(SudokuError::InvalidLength {
+            expected: 81,
+            found: cells,
+        });
+    }
+    Ok(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_candidates, read_grid, read_pencil_marks, write_candidates, write_grid};
+    use crate::board::{game_str_to_vec, Board};
+    use crate::error::SudokuError;
+
+    const GAME: &str =
+        "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+
+    fn board() -> Board {
+        let mut board = Board::new();
+        board.load_candidates(&game_str_to_vec(GAME).unwrap());
+        // a cell without pencil marks and one the player narrowed down
+        board.cells[1].restore((0, 0));
+        board.cells[2].remove_candidate(6);
+        board
+    }
+
+    #[test]
+    fn test_candidates() {
+        let board = board();
+        let candidates = write_candidates(&board);
+        assert_eq!(candidates.len(), 729);
+        assert_eq!(&candidates[..9], "...4.....");
+        assert_eq!(&candidates[9..18], ".........");
+        assert_eq!(&candidates[18..27], "12....7.9");
+
+        let other = read_candidates(&candidates).unwrap();
+        assert_eq!(other.backup(), board.backup());
+        assert_eq!(
+            read_pencil_marks(&format!("\n{candidates}\n"))
+                .unwrap()
+                .backup(),
+            board.backup()
+        );
+
+        let wrong = candidates.replacen("12", "21", 1);
+        assert_eq!(
+            read_candidates(&wrong).err(),
+            Some(SudokuError::InvalidCharacter {
+                position: 18,
+                char: '2'
+            })
+        );
+        assert_eq!(
+            read_candidates(&candidates[9..]).err(),
+            Some(SudokuError::InvalidLength {
+                expected: 729,
+                found: 720
+            })
+        );
+    }
+
+    #[test]
+    fn test_grid() {
+        let board = board();
+        let grid = write_grid(&board);
+        let lines: Vec<&str> = grid.lines().collect();
+        assert_eq!(lines.len(), 13);
+        assert!(lines[0].starts_with(".---"));
+        assert!(lines[4].starts_with(":---"));
+        assert!(lines[12].starts_with("'---"));
+        assert!(lines[1].starts_with("| 4  "));
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+
+        assert_eq!(read_grid(&grid).unwrap().backup(), board.backup());
+        assert_eq!(read_pencil_marks(&grid).unwrap().backup(), board.backup());
+
+        // a single candidate is not a placed value
+        let mut single = board.clone();
+        single.cells[3].restore((0, 0b100));
+        let grid = write_grid(&single);
+        assert!(grid.lines().nth(1).unwrap().contains(" 33 "));
+        let other = read_grid(&grid).unwrap();
+        assert_eq!(other.backup(), single.backup());
+        assert!(!other.cells[3].is_fixed());
+
+        let wrong = grid.replacen("| 4", "| x", 1);
+        let position = lines[0].len() + 3;
+        assert_eq!(
+            read_grid(&wrong).err(),
+            Some(SudokuError::InvalidCharacter {
+                position,
+                char: 'x'
+            })
+        );
+        let short = lines[..11].join("\n");
+        assert!(matches!(
+            read_grid(&short),
+            Err(SudokuError::InvalidLength {
+                expected: 81,
+                found: 72
+            })
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::board::{game_str_to_vec, Board};
+use crate::difficulty::Difficulty;
+use crate::error::SudokuError;
+use crate::logic::TechniqueKind;
+use crate::rating::rate;
+use crate::solver::unique_solution;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/**
+ * A generated puzzle and its unique solution, as 81 digits with '0' for the blanks, with the
+ * seed of the `Generator` that made it, so it can be generated again, and how hard it is.
+ */
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Puzzle {
+    pub givens: String,
+    pub solution: String,
+    pub seed: u64,
+    // the Sudoku Explainer rating, see `rating::rate`
+    pub rating: f32,
+    pub difficulty: Difficulty,
+    // how many times each technique was applied on the logical solve path
+    pub techniques: BTreeMap<TechniqueKind, usize>,
+    pub clues: usize,
+    // the time it took to generate the puzzle and its solution
+    pub generation_time: Duration,
+}
+
+impl Puzzle {
+    // solve and rate the givens of a puzzle, which must have a unique solution
+    pub fn new(
+        givens: String,
+        seed: u64,
+        generation_time: Duration,
+    ) -> Result<Puzzle, SudokuError> {
+        let game = game_str_to_vec(&givens)?;
+        let solution = unique_solution(&game)?
+            .iter()
+            .map(|value| value.to_string())
+            .collect();
+        let mut board = Board::new();
+        board.load_game(&game)?;
+        let rating = rate(&board)?;
+        Ok(Puzzle {
+            clues: game.iter().filter(|value| **value > 0).count(),
+            rating: rating.se,
+            difficulty: rating.difficulty(),
+            techniques: rating.techniques,
+            givens,
+            solution,
+            seed,
+            generation_time,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Puzzle;
+    use crate::difficulty::Difficulty;
+    use crate::error::SudokuError;
+    use crate::logic::TechniqueKind;
+    use std::time::Duration;
+
+    const GIVENS: &str =
+        "6....5.......217...58.....3....4..9......7.1..9.582.7.3.7..85...86..........7....";
+
+    #[test]
+    fn test_puzzle() {
+        let puzzle = Puzzle::new(GIVENS.to_string(), 7, Duration::from_millis(3)).unwrap();
+        assert_eq!(puzzle.clues, 24);
+        assert_eq!(
+            puzzle.solution,
+            "672835941934621758158794623765143892823967415491582376347218569286459137519376284"
+        );
+        assert_eq!(puzzle.rating, 3.2);
+        assert_eq!(puzzle.difficulty, Difficulty::Hard);
+        assert!(puzzle.techniques.contains_key(&TechniqueKind::XWing));
+
+        let ambiguous =
+            "6285194375794236184137865298.793214.3.214789.194865273781354962235691784946278351";
+        assert_eq!(
+            Puzzle::new(ambiguous.to_string(), 0, Duration::ZERO),
+            Err(SudokuError::MultipleSolutions)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::board::{game_str_to_vec, Board, UnitId};
+        use crate::variant::Variant;
+        use std::sync::Arc;
+
+        let puzzle = Puzzle::new(GIVENS.to_string(), 7, Duration::from_millis(3)).unwrap();
+        let json = serde_json::to_string(&puzzle).unwrap();
+        assert!(json.contains("\"XWing\""));
+        assert_eq!(serde_json::from_str::<Puzzle>(&json).unwrap(), puzzle);
+
+        let mut board = Board::new();
+        board.load_candidates(&game_str_to_vec(GIVENS).unwrap());
+        board.cells[0].set_readonly(true);
+        let json = serde_json::to_string(&board).unwrap();
+        let other: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(other.backup(), board.backup());
+        assert!(other.cells[0].is_readonly());
+        assert!(other.variants().is_empty());
+        assert!(serde_json::from_str::<Board>("{\"cells\": []}").is_err());
+        // a value that is not a digit would break every search of the board
+        let mut value = serde_json::to_value(&board).unwrap();
+        value["cells"][1]["value"] = 12.into();
+        let error = serde_json::from_value::<Board>(value).err().unwrap();
+        assert_eq!(error.to_string(), "12 in r1c2 is not a digit from 1 to 9");
+
+        // the variants come back with their rules, the other rules are not written
+        board.add_variant(Variant::Diagonal);
+        let json = serde_json::to_string(&board).unwrap();
+        assert!(json.contains("\"Diagonal\""));
+        let other: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(other.variants(), &[Variant::Diagonal]);
+        assert_eq!(other.constraints().len(), board.constraints().len());
+        board.add_constraint(Arc::new(UnitId::Window(0)));
+        assert!(serde_json::to_string(&board).is_err());
+    }
+}
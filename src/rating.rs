@@ -0,0 +1,221 @@
+use crate::board::{Board, UnitId};
+use crate::difficulty::Difficulty;
+use crate::error::SudokuError;
+use crate::logic::{LogicSolver, SolveStep, TechniqueKind};
+use crate::solver::unique_solution;
+use std::collections::BTreeMap;
+
+// Sudoku Explainer rating of the first guess, each further guess adds SE_GUESS_STEP
+const SE_GUESS: f32 = 7.5;
+const SE_GUESS_STEP: f32 = 0.5;
+const SE_MAX: f32 = 11.0;
+// HoDoKu score of a guess, its "brute force" step
+const HODOKU_GUESS: u32 = 10000;
+
+/**
+ * How hard a puzzle is along its logical solve path.
+ * `se` follows the Sudoku Explainer scale (1.0 - 11): the rating of the hardest step.
+ * `hodoku` follows HoDoKu: the sum of the scores of all the steps, so it also grows with the
+ * effort needed. When the techniques get stuck, a guess places the solution digit of the cell
+ * with the fewest candidates, and each guess is rated above any technique.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub se: f32,
+    pub hodoku: u32,
+    // the hardest technique used, `Guess` if a guess rates above it, None if the puzzle was
+    // already solved
+    pub hardest: Option<TechniqueKind>,
+    pub guesses: usize,
+    // how many times each technique was applied
+    pub techniques: BTreeMap<TechniqueKind, usize>,
+}
+
+impl Rating {
+    pub fn difficulty(&self) -> Difficulty {
+        match self.se {
+            se if se <= 1.5 => Difficulty::Easy,
+            se if se <= 2.8 => Difficulty::Medium,
+            se if se <= 4.0 => Difficulty::Hard,
+            se if se <= 6.6 => Difficulty::Expert,
+            _ => Difficulty::Extreme,
+        }
+    }
+}
+
+/**
+ * Rate the puzzle made of the fixed cells of the board, pencil marks are ignored. Only a puzzle
+ * with a unique solution can be rated.
+ */
+pub fn rate(board: &Board) -> Result<Rating, SudokuError> {
+    let game: Vec<u8> = board.cells.iter().map(|cell| cell.get_value()).collect();
+    let solution = unique_solution(&game)?;
+
+    let mut rating = Rating {
+        se: 1.0,
+        hodoku: 0,
+        hardest: None,
+        guesses: 0,
+        techniques: BTreeMap::new(),
+    };
+    let solver = LogicSolver::new();
+    let mut board = Board::new();
+    board.load_candidates(&game);
+    let mut hardest_se = 0.0;
+
+    loop {
+        for step in solver.solve_steps(&mut board) {
+            let se = se_rating(&step);
+            rating.se = rating.se.max(se);
+            if se > hardest_se {
+                hardest_se = se;
+                rating.hardest = Some(step.technique);
+            }
+            rating.hodoku += hodoku_score(&step);
+            *rating.techniques.entry(step.technique).or_insert(0) += 1;
+        }
+        if board.is_solved() {
+            return Ok(rating);
+        }
+
+        // stuck: guess the cell with the fewest candidates
+        let (index, _) = board.next_candidate_cell();
+        board.place(index, solution[index as usize]);
+        let se = (SE_GUESS + SE_GUESS_STEP * rating.guesses as f32).min(SE_MAX);
+        rating.se = rating.se.max(se);
+        if se > hardest_se {
+            hardest_se = se;
+            rating.hardest = Some(TechniqueKind::Guess);
+        }
+        rating.hodoku += HODOKU_GUESS;
+        rating.guesses += 1;
+    }
+}
+
+// the Sudoku Explainer rating of a step, W-Wing and colouring are rated like the chains they are
+pub fn se_rating(step: &SolveStep) -> f32 {
+    match step.technique {
+        TechniqueKind::HiddenSingle => match step.units.first() {
+            Some(UnitId::Block(_)) => 1.2,
+            _ => 1.5,
+        },
+        TechniqueKind::NakedSingle => 2.3,
+        TechniqueKind::PointingPair => 2.6,
+        TechniqueKind::BoxLineReduction => 2.8,
+        TechniqueKind::NakedPair => 3.0,
+        TechniqueKind::XWing => 3.2,
+        TechniqueKind::HiddenPair => 3.4,
+        TechniqueKind::NakedTriple => 3.6,
+        TechniqueKind::Swordfish => 3.8,
+        TechniqueKind::HiddenTriple => 4.0,
+        TechniqueKind::XYWing => 4.2,
+        TechniqueKind::XYZWing => 4.4,
+        TechniqueKind::WWing => 4.4,
+        TechniqueKind::UniqueRectangle => match step.digits.len() {
+            2 => 4.5,
+            _ => 4.6,
+        },
+        TechniqueKind::NakedQuad => 5.0,
+        TechniqueKind::Jellyfish => 5.2,
+        TechniqueKind::HiddenQuad => 5.4,
+        TechniqueKind::BugPlusOne => 5.6,
+        TechniqueKind::SimpleColouring => 6.6,
+        TechniqueKind::Guess => SE_GUESS,
+    }
+}
+
+// the default HoDoKu score of a step
+pub fn hodoku_score(step: &SolveStep) -> u32 {
+    match step.technique {
+        TechniqueKind::HiddenSingle => 14,
+        TechniqueKind::NakedSingle => 4,
+        TechniqueKind::PointingPair | TechniqueKind::BoxLineReduction => 50,
+        TechniqueKind::NakedPair => 60,
+        TechniqueKind::HiddenPair => 70,
+        TechniqueKind::NakedTriple => 80,
+        TechniqueKind::HiddenTriple => 100,
+        TechniqueKind::NakedQuad => 120,
+        TechniqueKind::HiddenQuad => 150,
+        TechniqueKind::XWing => 140,
+        TechniqueKind::Swordfish => 150,
+        TechniqueKind::Jellyfish => 160,
+        TechniqueKind::XYWing => 160,
+        TechniqueKind::XYZWing => 180,
+        TechniqueKind::WWing => 150,
+        TechniqueKind::SimpleColouring => 150,
+        TechniqueKind::UniqueRectangle => 100,
+        TechniqueKind::BugPlusOne => 100,
+        TechniqueKind::Guess => HODOKU_GUESS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rate;
+    use crate::board::{game_str_to_vec, Board};
+    use crate::difficulty::Difficulty;
+    use crate::error::SudokuError;
+    use crate::logic::TechniqueKind;
+
+    fn rate_game(game: &str) -> Result<super::Rating, SudokuError> {
+        let mut board = Board::new();
+        board.load_game(&game_str_to_vec(game).unwrap()).unwrap();
+        rate(&board)
+    }
+
+    #[test]
+    fn test_rate_singles() {
+        let r = rate_game(
+            ".......1..14...3.9...6......79...6..5...6......324.5........8.2.2.519.67....7....",
+        )
+        .unwrap();
+        assert_eq!(r.se, 1.5);
+        assert_eq!(r.hardest, Some(TechniqueKind::HiddenSingle));
+        assert_eq!(r.guesses, 0);
+        assert_eq!(r.difficulty(), Difficulty::Easy);
+        // one hidden single per empty cell
+        assert_eq!(r.hodoku, 14 * 57);
+    }
+
+    #[test]
+    fn test_rate_x_wing() {
+        let r = rate_game(
+            "6....5.......217...58.....3....4..9......7.1..9.582.7.3.7..85...86..........7....",
+        )
+        .unwrap();
+        assert_eq!(r.se, 3.2);
+        assert_eq!(r.hardest, Some(TechniqueKind::XWing));
+        assert_eq!(r.difficulty(), Difficulty::Hard);
+        assert!(r.techniques.contains_key(&TechniqueKind::XWing));
+    }
+
+    #[test]
+    fn test_rate_guesses() {
+        let r = rate_game(
+            "....4..6..6275.4..7......38..32...4..7.....8..8...13...1.5..........6...8...3..7.",
+        )
+        .unwrap();
+        assert_eq!(r.guesses, 1);
+        assert_eq!(r.se, 7.5);
+        assert_eq!(r.hardest, Some(TechniqueKind::Guess));
+        assert!(r.hodoku > 10000);
+        assert_eq!(r.difficulty(), Difficulty::Extreme);
+    }
+
+    #[test]
+    fn test_rate_no_solution() {
+        let r = rate_game(
+            "11...............................................................................",
+        );
+        assert!(matches!(r, Err(SudokuError::Conflict { digit: 1, .. })));
+        // r1c1 can only be 1, which r2c1 already has
+        let r = rate_game(
+            ".234567891.......................................................................",
+        );
+        assert_eq!(r, Err(SudokuError::NoSolution));
+        let r = rate_game(
+            "6285194375794236184137865298.793214.3.214789.194865273781354962235691784946278351",
+        );
+        assert_eq!(r, Err(SudokuError::MultipleSolutions));
+    }
+}
@@ -0,0 +1,44 @@
+use super::{game_of, Solver};
+use crate::bitboard::BitBoard;
+use crate::board::Board;
+use crate::constraint;
+use crate::error::SudokuError;
+
+/**
+ * The candidate backtracking search of `Board`, run on a `BitBoard`: the cell with the fewest
+ * candidates gets each of them in turn, and the singles that follow are placed at once. The
+ * extra rules of the board are propagated along, see `constraint::search`.
+ */
+pub struct Backtracking;
+
+impl Solver for Backtracking {
+    fn solve(&self, board: &mut Board) -> Result<(), SudokuError> {
+        let bits = BitBoard::from_game(&game_of(board)?).ok_or(SudokuError::NoSolution)?;
+        let mut solution = None;
+        constraint::search(&bits, board.constraints(), 1, &mut |found| {
+            solution = Some(*found)
+        });
+        solution.ok_or(SudokuError::NoSolution)?.write_to(board);
+        Ok(())
+    }
+
+    fn count_solutions(&self, board: &Board, limit: usize) -> Result<usize, SudokuError> {
+        Ok(BitBoard::from_game(&game_of(board)?).map_or(0, |bits| {
+            constraint::search(&bits, board.constraints(), limit, &mut |_| {})
+        }))
+    }
+
+    fn enumerate_solutions(
+        &self,
+        board: &Board,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, SudokuError> {
+        let mut solutions = Vec::new();
+        if let Some(bits) = BitBoard::from_game(&game_of(board)?) {
+            constraint::search(&bits, board.constraints(), limit, &mut |solution| {
+                solutions.push(solution.to_game())
+            });
+        }
+        Ok(solutions)
+    }
+}
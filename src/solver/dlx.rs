@@ -0,0 +1,241 @@
+use super::{game_of, Backtracking, Solver};
+use crate::board::{check_cells, Board};
+use crate::error::SudokuError;
+
+// one column per constraint: a cell has a digit, a row, a column and a block have each digit
+const COLUMNS: usize = 4 * 81;
+// one row per possible placement of a digit in a cell
+const ROWS: usize = 9 * 81;
+const ROOT: usize = 0;
+
+/**
+ * Knuth's Algorithm X on dancing links. The puzzle is the exact cover problem of choosing one
+ * placement per cell so that every row, column and block gets each digit exactly once. Covering
+ * a column unlinks its nodes in place and uncovering links them back, so backtracking costs no
+ * copy of the grid.
+ */
+pub struct Dlx;
+
+// the exact cover matrix only holds the classic rules, a board with extra ones is searched by
+// `Backtracking`
+impl Solver for Dlx {
+    fn count_solutions(&self, board: &Board, limit: usize) -> Result<usize, SudokuError> {
+        if !board.constraints().is_empty() {
+            return Backtracking.count_solutions(board, limit);
+        }
+        let mut count = 0;
+        if let Some(mut links) = Links::new(&game_of(board)?)? {
+            links.search(limit, &mut |_| count += 1);
+        }
+        Ok(count)
+    }
+
+    fn enumerate_solutions(
+        &self,
+        board: &Board,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, SudokuError> {
+        if !board.constraints().is_empty() {
+            return Backtracking.enumerate_solutions(board, limit);
+        }
+        let game = game_of(board)?;
+        let mut solutions = Vec::new();
+        if let Some(mut links) = Links::new(&game)? {
+            links.search(limit, &mut |placements| {
+                let mut solution = game.clone();
+                for row in placements {
+                    solution[row / 9] = (row % 9) as u8 + 1;
+                }
+                solutions.push(solution);
+            });
+        }
+        Ok(solutions)
+    }
+}
+
+/**
+ * The doubly linked nodes of the exact cover matrix. Node 0 is the root, nodes 1 to COLUMNS
+ * are the column headers, every placement row then has 4 nodes.
+ */
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    // the placement of a node, cell * 9 + digit - 1
+    row: Vec<usize>,
+    // number of nodes in each column, indexed by header
+    size: Vec<usize>,
+    // the placements chosen so far
+    partial: Vec<usize>,
+}
+
+impl Links {
+    // the matrix with the givens of the game already chosen, None if they conflict
+    fn new(game: &[u8]) -> Result<Option<Self>, SudokuError> {
+        // a value is a row of the matrix, one above 9 would land on another cell
+        check_cells(game)?;
+        let nodes = 1 + COLUMNS + 4 * ROWS;
+        let mut links = Links {
+            left: Vec::with_capacity(nodes),
+            right: Vec::with_capacity(nodes),
+            up: Vec::with_capacity(nodes),
+            down: Vec::with_capacity(nodes),
+            column: Vec::with_capacity(nodes),
+            row: Vec::with_capacity(nodes),
+            size: vec![0; COLUMNS + 1],
+            partial: Vec::with_capacity(81),
+        };
+
+        for header in 0..=COLUMNS {
+            links
+                .left
+                .push(if header == 0 { COLUMNS } else { header - 1 });
+            links
+                .right
+                .push(if header == COLUMNS { 0 } else { header + 1 });
+            links.up.push(header);
+            links.down.push(header);
+            links.column.push(header);
+            links.row.push(ROWS);
+        }
+
+        let mut first_nodes = vec![0; ROWS];
+        for (placement, first_node) in first_nodes.iter_mut().enumerate() {
+            let cell = placement / 9;
+            let digit = placement % 9;
+            let (r, c) = (cell / 9, cell % 9);
+            let b = (r / 3) * 3 + c / 3;
+            let columns = [
+                cell,
+                81 + r * 9 + digit,
+                162 + c * 9 + digit,
+                243 + b * 9 + digit,
+            ];
+
+            let first = links.column.len();
+            *first_node = first;
+            for (k, col) in columns.iter().enumerate() {
+                let header = col + 1;
+                let node = first + k;
+                links.left.push(if k == 0 { first + 3 } else { node - 1 });
+                links.right.push(if k == 3 { first } else { node + 1 });
+                links.up.push(links.up[header]);
+                links.down.push(header);
+                links.column.push(header);
+                links.row.push(placement);
+                let last = links.up[header];
+                links.down[last] = node;
+                links.up[header] = node;
+                links.size[header] += 1;
+            }
+        }
+
+        let mut covered = [false; COLUMNS + 1];
+        for (cell, value) in game.iter().enumerate() {
+            if *value == 0 {
+                continue;
+            }
+            let first = first_nodes[cell * 9 + *value as usize - 1];
+            for node in first..first + 4 {
+                let header = links.column[node];
+                if covered[header] {
+                    return Ok(None);
+                }
+                covered[header] = true;
+                links.cover(header);
+            }
+        }
+        Ok(Some(links))
+    }
+
+    fn cover(&mut self, header: usize) {
+        let (l, r) = (self.left[header], self.right[header]);
+        self.right[l] = r;
+        self.left[r] = l;
+
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = d;
+                self.up[d] = u;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = j;
+                self.up[d] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        let (l, r) = (self.left[header], self.right[header]);
+        self.right[l] = header;
+        self.left[r] = header;
+    }
+
+    /**
+     * Call `found` with the chosen placements of each solution, until `limit` solutions are
+     * found. Returns the number of solutions found.
+     */
+    fn search(&mut self, limit: usize, found: &mut dyn FnMut(&[usize])) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+        if self.right[ROOT] == ROOT {
+            found(&self.partial);
+            return 1;
+        }
+
+        // the column with the fewest placements left
+        let mut header = self.right[ROOT];
+        let mut j = self.right[header];
+        while j != ROOT {
+            if self.size[j] < self.size[header] {
+                header = j;
+            }
+            j = self.right[j];
+        }
+        if self.size[header] == 0 {
+            return 0;
+        }
+
+        let mut count = 0;
+        self.cover(header);
+        let mut i = self.down[header];
+        while i != header && count < limit {
+            self.partial.push(self.row[i]);
+            let mut j = self.right[i];
+            while j != i {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+
+            count += self.search(limit - count, found);
+
+            let mut j = self.left[i];
+            while j != i {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            self.partial.pop();
+            i = self.down[i];
+        }
+        self.uncover(header);
+        count
+    }
+}
@@ -0,0 +1,192 @@
+//! Complete solvers, which find every solution of a grid by search. They share the `Solver`
+//! trait so the backtracking search of `Board` and the exact-cover search can replace each
+//! other, see `logic` for solving the way a human does.
+
+use crate::board::{check_cells, check_game, Board};
+use crate::error::SudokuError;
+
+mod backtracking;
+mod dlx;
+mod parallel;
+
+pub use backtracking::Backtracking;
+pub use dlx::Dlx;
+pub use parallel::Parallel;
+
+/**
+ * A solver reads the fixed cells of a board as the puzzle, the candidates are ignored.
+ * Solutions are returned as 81 values, in the format accepted by `Board::load_game`. A search
+ * interrupted before it completes fails with `SudokuError::Cancelled`, see
+ * `Parallel::cancel_flag`, so it is never mistaken for a proof that there is no solution.
+ */
+pub trait Solver {
+    /**
+     * Fill the board with a solution of its puzzle, like `Board::solve`. The board is left
+     * untouched when there is no solution.
+     */
+    fn solve(&self, board: &mut Board) -> Result<(), SudokuError> {
+        match self.enumerate_solutions(board, 1)?.pop() {
+            Some(solution) => {
+                for (i, value) in solution.into_iter().enumerate() {
+                    board.cells[i].restore((value, 0));
+                }
+                Ok(())
+            }
+            None => Err(SudokuError::NoSolution),
+        }
+    }
+
+    /**
+     * Count the solutions up to `limit`, so a result equal to `limit` means there are at least
+     * `limit` solutions.
+     */
+    fn count_solutions(&self, board: &Board, limit: usize) -> Result<usize, SudokuError> {
+        Ok(self.enumerate_solutions(board, limit)?.len())
+    }
+
+    // at most `limit` solutions, in the order they are found
+    fn enumerate_solutions(&self, board: &Board, limit: usize)
+        -> Result<Vec<Vec<u8>>, SudokuError>;
+}
+
+// the fixed cells of a board as a game, each a digit from 1 to 9
+pub(crate) fn game_of(board: &Board) -> Result<Vec<u8>, SudokuError> {
+    let game: Vec<u8> = board.cells.iter().map(|cell| cell.get_value()).collect();
+    check_cells(&game)?;
+    Ok(game)
+}
+
+// the solution of a game, which must be free of conflicts and have exactly one solution
+pub(crate) fn unique_solution(game: &[u8]) -> Result<Vec<u8>, SudokuError> {
+    check_game(game)?;
+    let mut board = Board::new();
+    board.load_game(game)?;
+    let mut solutions = Dlx.enumerate_solutions(&board, 2)?;
+    match (solutions.pop(), solutions.is_empty()) {
+        (Some(solution), true) => Ok(solution),
+        (Some(_), false) => Err(SudokuError::MultipleSolutions),
+        (None, _) => Err(SudokuError::NoSolution),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backtracking, Dlx, Parallel, Solver};
+    use crate::board::{game_str_to_vec, Board};
+    use crate::constraint::{AllDifferent, Constraint};
+    use crate::error::SudokuError;
+    use std::sync::Arc;
+
+    fn load(game: &str) -> Board {
+        let mut board = Board::new();
+        board.load_game(&game_str_to_vec(game).unwrap()).unwrap();
+        board
+    }
+
+    fn solvers() -> Vec<Box<dyn Solver>> {
+        vec![
+            Box::new(Backtracking),
+            Box::new(Dlx),
+            Box::new(Parallel::new(4)),
+        ]
+    }
+
+    #[test]
+    fn test_solve() {
+        let game =
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        for solver in solvers() {
+            let mut board = load(game);
+            assert_eq!(solver.solve(&mut board), Ok(()));
+            assert!(board.is_solved());
+            assert_eq!(
+                board.serialize(),
+                "417369825632158947958724316825437169791586432346912758289643571573291684164875293"
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_solution() {
+        // r1c1 can only be 1, which r2c1 already has
+        let game =
+            ".234567891.......................................................................";
+        for solver in solvers() {
+            let mut board = load(game);
+            assert_eq!(solver.solve(&mut board), Err(SudokuError::NoSolution));
+            assert_eq!(board.serialize(), game);
+            assert_eq!(solver.count_solutions(&board, 2), Ok(0));
+        }
+        // two 1s in a row
+        let board = load(
+            "11...............................................................................",
+        );
+        for solver in solvers() {
+            assert!(solver.enumerate_solutions(&board, 2).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_invalid_digit() {
+        // a value above 9 set by hand is refused rather than read as another cell
+        let mut board = Board::new();
+        board.cells[40].restore((12, 0));
+        let error = SudokuError::InvalidDigit {
+            cell: 40,
+            digit: 12,
+        };
+        for solver in solvers() {
+            assert_eq!(solver.count_solutions(&board, 2), Err(error.clone()));
+            assert_eq!(solver.enumerate_solutions(&board, 2), Err(error.clone()));
+            assert_eq!(solver.solve(&mut board.clone()), Err(error.clone()));
+        }
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let game =
+            "6285194375794236184137865298.793214.3.214789.194865273781354962235691784946278351";
+        let board = load(game);
+        let empty = Board::new();
+        for solver in solvers() {
+            assert_eq!(solver.count_solutions(&board, 10), Ok(2));
+            assert_eq!(solver.count_solutions(&board, 1), Ok(1));
+            assert_eq!(solver.count_solutions(&empty, 5), Ok(5));
+            assert_eq!(solver.count_solutions(&empty, 0), Ok(0));
+        }
+    }
+
+    #[test]
+    fn test_enumerate_solutions() {
+        let game =
+            "6285194375794236184137865298.793214.3.214789.194865273781354962235691784946278351";
+        let board = load(game);
+        let mut expected = Backtracking.enumerate_solutions(&board, 10).unwrap();
+        let mut found = Dlx.enumerate_solutions(&board, 10).unwrap();
+        expected.sort();
+        found.sort();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found, expected);
+
+        for solution in Dlx.enumerate_solutions(&Board::new(), 20).unwrap() {
+            assert!(Board::is_valid_game(&solution));
+            assert!(!solution.contains(&0));
+        }
+    }
+
+    #[test]
+    fn test_constraints() {
+        let mut board = Board::new();
+        let diagonal = AllDifferent::new((0..9).map(|i| i * 10).collect()).unwrap();
+        board.add_constraint(Arc::new(diagonal.clone()));
+        for solver in solvers() {
+            let solutions = solver.enumerate_solutions(&board, 5).unwrap();
+            assert_eq!(solutions.len(), 5);
+            assert!(solutions.iter().all(|solution| diagonal.check(solution)));
+
+            let mut solved = board.clone();
+            assert_eq!(solver.solve(&mut solved), Ok(()));
+            assert!(solved.is_solved());
+        }
+    }
+}
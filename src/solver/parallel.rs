@@ -0,0 +1,304 @@
+use super::{game_of, Backtracking, Solver};
+use crate::bitboard::BitBoard;
+use crate::board::Board;
+use crate::error::SudokuError;
+use crate::logic::digits;
+use crossbeam_deque::{Injector, Steal, Stealer, Worker};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/**
+ * A search spread over threads. Each thread runs depth first on its own deque of boards, and a
+ * thread running out of boards steals the oldest one of another thread, which is the biggest
+ * part of the search left there. The search ends when the limit is reached, when the cancel
+ * flag is raised, or when every thread is idle, which proves the search space is exhausted.
+ */
+pub struct Parallel {
+    threads: usize,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Default for Parallel {
+    // one thread per cpu
+    fn default() -> Self {
+        Self::new(num_cpus::get())
+    }
+}
+
+impl Parallel {
+    pub fn new(threads: usize) -> Self {
+        Parallel {
+            threads: threads.max(1),
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /**
+     * Raising the flag stops the running search of this solver, or the next one if none is
+     * running, which then fails with `SudokuError::Cancelled`. The flag is lowered again when
+     * the search ends, so the searches after it run normally.
+     */
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancel)
+    }
+
+    /**
+     * Call `found` with every solution of the board until `limit` of them are found, like
+     * `BitBoard::search`. Solutions come in no particular order. Returns how many were found,
+     * or `SudokuError::Cancelled` if the flag stopped the search before it completed.
+     */
+    pub fn search(
+        &self,
+        board: &BitBoard,
+        limit: usize,
+        found: &(dyn Fn(&BitBoard) + Sync),
+    ) -> Result<usize, SudokuError> {
+        let cancelled = self.cancel.load(Ordering::SeqCst);
+        if cancelled || limit == 0 {
+            self.cancel.store(false, Ordering::SeqCst);
+            return if cancelled {
+                Err(SudokuError::Cancelled)
+            } else {
+                Ok(0)
+            };
+        }
+        let injector = Injector::new();
+        injector.push(*board);
+        let workers: Vec<Worker<BitBoard>> =
+            (0..self.threads).map(|_| Worker::new_lifo()).collect();
+        let stealers: Vec<Stealer<BitBoard>> = workers.iter().map(|w| w.stealer()).collect();
+        let shared = Shared {
+            injector,
+            stealers,
+            count: AtomicUsize::new(0),
+            idle: AtomicUsize::new(0),
+            done: AtomicBool::new(false),
+            exhausted: AtomicBool::new(false),
+            cancel: &self.cancel,
+            threads: self.threads,
+            limit,
+            found,
+        };
+
+        thread::scope(|scope| {
+            for (id, worker) in workers.into_iter().enumerate() {
+                let shared = &shared;
+                scope.spawn(move || shared.run(id, worker));
+            }
+        });
+        self.cancel.store(false, Ordering::SeqCst);
+        // a search stopped by the flag may have missed solutions
+        if shared.done.load(Ordering::SeqCst) || shared.exhausted.load(Ordering::SeqCst) {
+            Ok(shared.count.load(Ordering::SeqCst).min(limit))
+        } else {
+            Err(SudokuError::Cancelled)
+        }
+    }
+}
+
+// the state of a search, shared by its threads
+struct Shared<'a> {
+    injector: Injector<BitBoard>,
+    stealers: Vec<Stealer<BitBoard>>,
+    // solutions found, may go past the limit
+    count: AtomicUsize,
+    // threads which found no board to search
+    idle: AtomicUsize,
+    // the limit is reached
+    done: AtomicBool,
+    // every thread was idle, the whole search space was seen
+    exhausted: AtomicBool,
+    cancel: &'a AtomicBool,
+    threads: usize,
+    limit: usize,
+    found: &'a (dyn Fn(&BitBoard) + Sync),
+}
+
+impl Shared<'_> {
+    fn stopped(&self) -> bool {
+        self.done.load(Ordering::Relaxed) || self.cancel.load(Ordering::Relaxed)
+    }
+
+    fn run(&self, id: usize, worker: Worker<BitBoard>) {
+        while !self.stopped() {
+            let board = match worker.pop().or_else(|| self.steal(id, &worker)) {
+                Some(board) => board,
+                None => match self.wait(id, &worker) {
+                    Some(board) => board,
+                    None => return,
+                },
+            };
+            self.expand(&board, &worker);
+        }
+    }
+
+    /**
+     * Wait for a board to steal, None once the search is over. A thread counts as idle while
+     * it holds no board, and only stops counting just before it tries to steal one, so all the
+     * threads being idle at once means every deque is empty.
+     */
+    fn wait(&self, id: usize, worker: &Worker<BitBoard>) -> Option<BitBoard> {
+        self.idle.fetch_add(1, Ordering::SeqCst);
+        loop {
+            if self.idle.load(Ordering::SeqCst) == self.threads {
+                self.exhausted.store(true, Ordering::SeqCst);
+                return None;
+            }
+            if self.stopped() {
+                return None;
+            }
+            self.idle.fetch_sub(1, Ordering::SeqCst);
+            if let Some(board) = self.steal(id, worker) {
+                return Some(board);
+            }
+            self.idle.fetch_add(1, Ordering::SeqCst);
+            thread::yield_now();
+        }
+    }
+
+    fn steal(&self, id: usize, worker: &Worker<BitBoard>) -> Option<BitBoard> {
+        loop {
+            let mut retry = false;
+            match self.injector.steal_batch_and_pop(worker) {
+                Steal::Success(board) => return Some(board),
+                Steal::Retry => retry = true,
+                Steal::Empty => {}
+            }
+            for (i, stealer) in self.stealers.iter().enumerate() {
+                if i == id {
+                    continue;
+                }
+                match stealer.steal() {
+                    Steal::Success(board) => return Some(board),
+                    Steal::Retry => retry = true,
+                    Steal::Empty => {}
+                }
+            }
+            if !retry {
+                return None;
+            }
+        }
+    }
+
+    // report a solution, or push a board for each candidate of the cell with the fewest
+    fn expand(&self, board: &BitBoard, worker: &Worker<BitBoard>) {
+        match board.next_cell() {
+            None => {
+                let index = self.count.fetch_add(1, Ordering::SeqCst);
+                if index < self.limit {
+                    (self.found)(board);
+                }
+                if index + 1 >= self.limit {
+                    self.done.store(true, Ordering::SeqCst);
+                }
+            }
+            Some((index, candidates)) => {
+                // pushed last to be searched first, like the sequential search
+                for value in digits(candidates).into_iter().rev() {
+                    let mut next = *board;
+                    if next.assign(index, value) {
+                        worker.push(next);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// the workers search without the extra rules of a board, which `Backtracking` handles instead
+impl Solver for Parallel {
+    fn count_solutions(&self, board: &Board, limit: usize) -> Result<usize, SudokuError> {
+        if !board.constraints().is_empty() {
+            return Backtracking.count_solutions(board, limit);
+        }
+        match BitBoard::from_game(&game_of(board)?) {
+            Some(bits) => self.search(&bits, limit, &|_| {}),
+            None => Ok(0),
+        }
+    }
+
+    fn enumerate_solutions(
+        &self,
+        board: &Board,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, SudokuError> {
+        if !board.constraints().is_empty() {
+            return Backtracking.enumerate_solutions(board, limit);
+        }
+        let solutions = Mutex::new(Vec::new());
+        if let Some(bits) = BitBoard::from_game(&game_of(board)?) {
+            self.search(&bits, limit, &|solution| {
+                solutions.lock().unwrap().push(solution.to_game())
+            })?;
+        }
+        Ok(solutions.into_inner().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Parallel;
+    use crate::bitboard::BitBoard;
+    use crate::board::{game_str_to_vec, Board};
+    use crate::error::SudokuError;
+    use crate::solver::Solver;
+    use std::collections::HashSet;
+    use std::sync::atomic::Ordering;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_no_solution() {
+        // consistent givens, but r1c2 has to be 1 for the grid to be solvable
+        let game =
+            "49....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let board = BitBoard::from_game(&game_str_to_vec(game).unwrap()).unwrap();
+        for threads in [1, 2, 8] {
+            assert_eq!(Parallel::new(threads).search(&board, 10, &|_| {}), Ok(0));
+        }
+    }
+
+    #[test]
+    fn test_count_across_threads() {
+        let game =
+            "6285194375794236184137865298.793214.3.214789.194865273781354962235691784946278351";
+        let board = BitBoard::from_game(&game_str_to_vec(game).unwrap()).unwrap();
+        let parallel = Parallel::new(8);
+        assert_eq!(parallel.search(&board, 10, &|_| {}), Ok(2));
+        assert_eq!(parallel.search(&board, 1, &|_| {}), Ok(1));
+        assert_eq!(parallel.search(&board, 0, &|_| {}), Ok(0));
+
+        let solutions = Mutex::new(HashSet::new());
+        let count = parallel.search(&BitBoard::new(), 500, &|solution| {
+            solutions.lock().unwrap().insert(solution.to_game());
+        });
+        assert_eq!(count, Ok(500));
+        assert_eq!(solutions.lock().unwrap().len(), 500);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let parallel = Parallel::new(4);
+        parallel.cancel_flag().store(true, Ordering::SeqCst);
+        assert_eq!(
+            parallel.search(&BitBoard::new(), usize::MAX, &|_| {}),
+            Err(SudokuError::Cancelled)
+        );
+        // the next search runs again
+        assert_eq!(parallel.search(&BitBoard::new(), 3, &|_| {}), Ok(3));
+
+        // cancelled while it runs, from the callback of the first solution
+        let flag = parallel.cancel_flag();
+        let cancelled = parallel.search(&BitBoard::new(), usize::MAX, &|_| {
+            flag.store(true, Ordering::SeqCst)
+        });
+        assert_eq!(cancelled, Err(SudokuError::Cancelled));
+        let board = Board::new();
+        assert_eq!(parallel.count_solutions(&board, 2), Ok(2));
+        flag.store(true, Ordering::SeqCst);
+        assert_eq!(
+            parallel.count_solutions(&board, 2),
+            Err(SudokuError::Cancelled)
+        );
+    }
+}
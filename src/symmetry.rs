@@ -0,0 +1,166 @@
+/**
+ * The canonical form of a solution grid: the smallest grid, read row by row, among all the
+ * grids it can be turned into by the moves that keep a sudoku valid. Those are transposing,
+ * swapping bands or stacks, swapping rows inside a band or columns inside a stack, and
+ * relabelling the digits. Two grids are equivalent when they have the same canonical form.
+ * The grid must be complete.
+ */
+pub fn canonical_form(grid: &[u8]) -> Vec<u8> {
+    let orders = column_orders();
+    let mut best: Option<[u32; 9]> = None;
+    for transposed in [false, true] {
+        let mut rows = [[0u8; 9]; 9];
+        for r in 0..9 {
+            for c in 0..9 {
+                rows[r][c] = if transposed {
+                    grid[c * 9 + r]
+                } else {
+                    grid[r * 9 + c]
+                };
+            }
+        }
+        for top in 0..9 {
+            for columns in &orders {
+                canonical_rows(&rows, top, columns, &mut best);
+            }
+        }
+    }
+
+    let mut result = Vec::with_capacity(81);
+    for code in best.unwrap() {
+        for k in (0..9).rev() {
+            result.push((code / 10u32.pow(k) % 10) as u8);
+        }
+    }
+    result
+}
+
+// the 1296 orders of the columns that keep the stacks together
+fn column_orders() -> Vec<[usize; 9]> {
+    const PERMUTATIONS: [[usize; 3]; 6] = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    let mut result = Vec::with_capacity(1296);
+    for stacks in PERMUTATIONS {
+        for p0 in PERMUTATIONS {
+            for p1 in PERMUTATIONS {
+                for p2 in PERMUTATIONS {
+                    let inner = [p0, p1, p2];
+                    let mut order = [0; 9];
+                    for j in 0..9 {
+                        order[j] = stacks[j / 3] * 3 + inner[j / 3][j % 3];
+                    }
+                    result.push(order);
+                }
+            }
+        }
+    }
+    result
+}
+
+/**
+ * With row `top` first and the columns in the given order, the digits are relabelled so the
+ * first row reads 123456789. Each other row then reads as a 9 digit number, and the smallest
+ * grid sorts the rows inside each band and the two other bands. Keeps it in `best` if smaller.
+ */
+fn canonical_rows(
+    rows: &[[u8; 9]; 9],
+    top: usize,
+    columns: &[usize; 9],
+    best: &mut Option<[u32; 9]>,
+) {
+    let mut label = [0u32; 10];
+    for j in 0..9 {
+        label[rows[top][columns[j]] as usize] = j as u32 + 1;
+    }
+    let code = |r: usize| -> u32 {
+        columns
+            .iter()
+            .fold(0, |acc, c| acc * 10 + label[rows[r][*c] as usize])
+    };
+
+    let band = top / 3;
+    let first = band * 3;
+    let (a, b) = match top - first {
+        0 => (code(first + 1), code(first + 2)),
+        1 => (code(first), code(first + 2)),
+        _ => (code(first), code(first + 1)),
+    };
+    let second = a.min(b);
+    // the second row decides most of the comparisons
+    if let Some(best) = best {
+        if second > best[1] {
+            return;
+        }
+    }
+
+    let mut others = [[0u32; 3]; 2];
+    for (k, other) in (0..3).filter(|other| *other != band).enumerate() {
+        others[k] = [code(other * 3), code(other * 3 + 1), code(other * 3 + 2)];
+        others[k].sort_unstable();
+    }
+    others.sort_unstable();
+
+    let mut codes = [123456789, second, a.max(b), 0, 0, 0, 0, 0, 0];
+    codes[3..6].copy_from_slice(&others[0]);
+    codes[6..9].copy_from_slice(&others[1]);
+    if best.is_none_or(|best| codes < best) {
+        *best = Some(codes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::canonical_form;
+    use crate::board::{game_str_to_vec, Board};
+
+    const GRID: &str =
+        "417369825632158947958724316825437169791586432346912758289643571573291684164875293";
+
+    #[test]
+    fn test_canonical_form() {
+        let grid = game_str_to_vec(GRID).unwrap();
+        let canonical = canonical_form(&grid);
+        assert!(Board::is_valid_game(&canonical));
+        assert_eq!(&canonical[..9], &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(canonical_form(&canonical), canonical);
+
+        // transpose, swap the first two bands, the last two columns and relabel 1 <-> 9
+        let mut other = vec![0; 81];
+        for r in 0..9 {
+            for c in 0..9 {
+                let row = if r < 6 { (r + 3) % 6 } else { r };
+                let col = match c {
+                    7 => 8,
+                    8 => 7,
+                    c => c,
+                };
+                let value = grid[col * 9 + row];
+                other[r * 9 + c] = match value {
+                    1 => 9,
+                    9 => 1,
+                    v => v,
+                };
+            }
+        }
+        assert_ne!(other, grid);
+        assert_eq!(canonical_form(&other), canonical);
+    }
+
+    #[test]
+    fn test_different_grids() {
+        let grid = game_str_to_vec(GRID).unwrap();
+        // a very regular grid, made of shifted rows
+        let other = game_str_to_vec(
+            "123456789456789123789123456214365897365897214897214365531642978642978531978531642",
+        )
+        .unwrap();
+        assert!(Board::is_valid_game(&other));
+        assert_ne!(canonical_form(&grid), canonical_form(&other));
+    }
+}
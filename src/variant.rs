@@ -0,0 +1,81 @@
+//! Variants that add units to the classic rules: the two main diagonals of Sudoku-X and the four
+//! windows of Windoku, each holding every digit once. A variant is played with
+//! `Board::add_variant` and generated with `Generator::with_variants`, and both can be combined.
+
+use crate::board::UnitId;
+use crate::constraint::Constraint;
+use std::sync::Arc;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variant {
+    // Sudoku-X, the two main diagonals
+    Diagonal,
+    // Windoku or Hyper Sudoku, four extra 3x3 windows
+    Windoku,
+}
+
+impl Variant {
+    pub fn all() -> [Variant; 2] {
+        [Variant::Diagonal, Variant::Windoku]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Diagonal => "Sudoku-X",
+            Variant::Windoku => "Windoku",
+        }
+    }
+
+    // the units the variant adds
+    pub fn units(&self) -> Vec<UnitId> {
+        match self {
+            Variant::Diagonal => (0..2).map(UnitId::Diagonal).collect(),
+            Variant::Windoku => (0..4).map(UnitId::Window).collect(),
+        }
+    }
+
+    // one rule per added unit
+    pub fn constraints(&self) -> Vec<Arc<dyn Constraint>> {
+        self.units()
+            .into_iter()
+            .map(|unit| Arc::new(unit) as Arc<dyn Constraint>)
+            .collect()
+    }
+
+    // true if a cell belongs to one of the added units, which the UI shades
+    pub fn contains(&self, index: u8) -> bool {
+        self.units()
+            .iter()
+            .any(|unit| unit.cells().contains(&index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Variant;
+    use crate::board::{cell_name, UnitId};
+
+    #[test]
+    fn test_units() {
+        let diagonals: Vec<Vec<String>> = Variant::Diagonal
+            .units()
+            .iter()
+            .map(|unit| unit.cells().iter().map(|&cell| cell_name(cell)).collect())
+            .collect();
+        assert_eq!(diagonals[0][8], "r9c9");
+        assert_eq!(diagonals[1][0], "r1c9");
+        assert_eq!(diagonals[1][8], "r9c1");
+
+        let windows = Variant::Windoku.units();
+        assert_eq!(windows.len(), 4);
+        assert_eq!(UnitId::Window(3).to_string(), "window 4");
+        assert_eq!(cell_name(windows[3].cells()[8]), "r8c8");
+
+        assert!(Variant::Windoku.contains(10));
+        assert!(!Variant::Windoku.contains(13));
+        assert!(Variant::Diagonal.contains(40));
+        assert!(!Variant::Diagonal.contains(41));
+        assert_eq!(Variant::Diagonal.constraints().len(), 2);
+    }
+}